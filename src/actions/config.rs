@@ -0,0 +1,33 @@
+use crate::data::action::Action;
+use crate::data::common::event::Event;
+use crate::state::management::state_manager::StateManager;
+use crate::win_api;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub struct ReloadConfig {}
+
+impl Action for ReloadConfig {
+    // The main loop owns the keybinds, so the reload happens once it receives the event
+    fn execute(&self, _state_manager: &mut StateManager) {
+        win_api::window::send_event_message(Event::reload_config());
+    }
+}
+
+impl FromStr for ReloadConfig {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.eq("RELOAD_CONFIG") {
+            return Err(());
+        }
+        Ok(ReloadConfig {})
+    }
+}
+
+impl Display for ReloadConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "reload_config")
+    }
+}
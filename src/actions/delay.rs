@@ -0,0 +1,36 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+// Pause between the actions of a keybind, written as delay_<milliseconds>
+pub struct Delay {
+    pub duration: Duration,
+}
+
+impl Action for Delay {
+    // The KeyManager holds back the actions following a delay, so there's nothing to do here
+    fn execute(&self, _state_manager: &mut StateManager) {}
+}
+
+impl FromStr for Delay {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up = input.to_ascii_uppercase();
+        let milliseconds = match input_up.strip_prefix("DELAY_") {
+            Some(milliseconds) => u64::from_str(milliseconds).map_err(|_| ())?,
+            None => return Err(()),
+        };
+        Ok(Delay {
+            duration: Duration::from_millis(milliseconds),
+        })
+    }
+}
+
+impl Display for Delay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "delay_{}", self.duration.as_millis())
+    }
+}
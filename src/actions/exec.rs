@@ -0,0 +1,218 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use log::error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/*
+   Start a program, written as 'exec: <command line>' with optional settings in parentheses:
+   exec(workspace=3, cwd=C:\dev, env=NAME=value): wt.exe
+   The program is started directly rather than through a shell, builtins and redirections need
+   'cmd /c' in front of the command line
+*/
+pub struct Exec {
+    pub command: String,
+    pub cwd: Option<String>,
+    pub env: Vec<(String, String)>,
+    // Workspace the first window of the program opens on, the current one when not set
+    pub workspace: Option<usize>,
+}
+
+impl Action for Exec {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.exec(self);
+    }
+}
+
+impl FromStr for Exec {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if !input.to_ascii_uppercase().starts_with("EXEC") {
+            return Err(());
+        }
+        let rest = input["EXEC".len()..].trim_start();
+        let (options, command) = match rest.strip_prefix('(') {
+            Some(options) => match options.split_once(')') {
+                Some((options, command)) => (options, command.trim_start()),
+                None => return Err(()),
+            },
+            None => ("", rest),
+        };
+        let command = match command.strip_prefix(':') {
+            Some(command) if !command.trim().is_empty() => command.trim(),
+            _ => return Err(()),
+        };
+        let mut exec = Exec {
+            command: command.to_string(),
+            cwd: None,
+            env: Vec::new(),
+            workspace: None,
+        };
+        for option in options
+            .split(',')
+            .filter(|option| !option.trim().is_empty())
+        {
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name.trim().to_ascii_lowercase(), value.trim()),
+                None => {
+                    error!(
+                        "Expected '<option>=<value>' in exec option {}",
+                        option.trim()
+                    );
+                    return Err(());
+                }
+            };
+            match name.as_str() {
+                "workspace" => match usize::from_str(value) {
+                    Ok(workspace) => exec.workspace = Some(workspace),
+                    _ => {
+                        error!("Unable to parse workspace id from {}", value);
+                        return Err(());
+                    }
+                },
+                "cwd" => exec.cwd = Some(value.to_string()),
+                "env" => match value.split_once('=') {
+                    Some((variable, value)) => exec
+                        .env
+                        .push((variable.trim().to_string(), value.trim().to_string())),
+                    None => {
+                        error!(
+                            "Expected 'env=<name>=<value>' in exec option {}",
+                            option.trim()
+                        );
+                        return Err(());
+                    }
+                },
+                _ => {
+                    error!("Unknown exec option {}", name);
+                    return Err(());
+                }
+            }
+        }
+        Ok(exec)
+    }
+}
+
+impl Display for Exec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Written back the way the config accepts it, options included
+        let mut options: Vec<String> = Vec::new();
+        if let Some(workspace) = self.workspace {
+            options.push(format!("workspace={}", workspace));
+        }
+        if let Some(cwd) = &self.cwd {
+            options.push(format!("cwd={}", cwd));
+        }
+        for (variable, value) in self.env.iter() {
+            options.push(format!("env={}={}", variable, value));
+        }
+        match options.is_empty() {
+            true => write!(f, "exec: {}", self.command),
+            false => write!(f, "exec({}): {}", options.join(", "), self.command),
+        }
+    }
+}
+
+// Split a command line into the program and its arguments, double quotes group words together
+pub fn split_command_line(command: &str) -> Vec<String> {
+    let mut arguments: Vec<String> = Vec::new();
+    let mut argument = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for character in command.chars() {
+        match character {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            _ if character.is_whitespace() && !quoted => {
+                if started {
+                    arguments.push(argument.clone());
+                    argument.clear();
+                    started = false;
+                }
+            }
+            _ => {
+                argument.push(character);
+                started = true;
+            }
+        }
+    }
+    if started {
+        arguments.push(argument);
+    }
+    return arguments;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::actions::exec::{split_command_line, Exec};
+
+    #[test]
+    fn commands_keep_their_colons_and_quotes() {
+        let exec = Exec::from_str("exec: cmd /c dir > C:\\files.txt").unwrap();
+        assert_eq!(exec.command, "cmd /c dir > C:\\files.txt");
+        assert_eq!(exec.workspace, None);
+        assert!(exec.env.is_empty());
+        let exec = Exec::from_str("EXEC :  \"C:\\Program Files\\app.exe\" --flag ").unwrap();
+        assert_eq!(exec.command, "\"C:\\Program Files\\app.exe\" --flag");
+    }
+
+    #[test]
+    fn options_are_read_from_the_parentheses() {
+        let exec = Exec::from_str(
+            "exec(workspace=2, cwd=C:\\dev, env=RUST_LOG=debug, env=A = b): code.exe .",
+        )
+        .unwrap();
+        assert_eq!(exec.command, "code.exe .");
+        assert_eq!(exec.workspace, Some(2));
+        assert_eq!(exec.cwd.as_deref(), Some("C:\\dev"));
+        assert_eq!(
+            exec.env,
+            vec![
+                (String::from("RUST_LOG"), String::from("debug")),
+                (String::from("A"), String::from("b")),
+            ]
+        );
+        assert_eq!(
+            exec.to_string(),
+            "exec(workspace=2, cwd=C:\\dev, env=RUST_LOG=debug, env=A=b): code.exe ."
+        );
+    }
+
+    #[test]
+    fn workspace_zero_is_kept_for_workspace_ten() {
+        let exec = Exec::from_str("exec(workspace=0): wt.exe").unwrap();
+        assert_eq!(exec.workspace, Some(0));
+        assert!(Exec::from_str("exec(workspace=-1): wt.exe").is_err());
+        assert!(Exec::from_str("exec(workspace=two): wt.exe").is_err());
+    }
+
+    #[test]
+    fn malformed_execs_are_rejected() {
+        assert!(Exec::from_str("exec(workspace=2: wt.exe").is_err());
+        assert!(Exec::from_str("exec(env=RUST_LOG): wt.exe").is_err());
+        assert!(Exec::from_str("exec(shell=pwsh): wt.exe").is_err());
+        assert!(Exec::from_str("exec(workspace): wt.exe").is_err());
+        assert!(Exec::from_str("exec:   ").is_err());
+        assert!(Exec::from_str("exec wt.exe").is_err());
+        assert!(Exec::from_str("execute: wt.exe").is_err());
+    }
+
+    #[test]
+    fn command_lines_split_on_unquoted_whitespace() {
+        assert_eq!(
+            split_command_line("wt.exe  -d \"C:\\My Projects\" --title=\"a b\""),
+            vec!["wt.exe", "-d", "C:\\My Projects", "--title=a b"]
+        );
+        assert_eq!(
+            split_command_line("app.exe \"\" last"),
+            vec!["app.exe", "", "last"]
+        );
+        assert!(split_command_line("   ").is_empty());
+    }
+}
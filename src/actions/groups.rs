@@ -2,6 +2,7 @@ use crate::data::action::Action;
 use crate::data::common::axis::Axis;
 use crate::state::management::state_manager::StateManager;
 use log::error;
+use std::fmt::{Display, Formatter};
 use std::process::exit;
 use std::str::FromStr;
 
@@ -34,3 +35,55 @@ impl FromStr for SetSplitAxis {
         })
     }
 }
+
+pub struct ToggleGroupStacking {}
+
+impl Action for ToggleGroupStacking {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.toggle_group_stacking();
+    }
+}
+
+impl FromStr for ToggleGroupStacking {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up = input.to_ascii_uppercase();
+        if !input_up.contains("TOGGLE_GROUP_STACKING") {
+            return Err(());
+        }
+        Ok(ToggleGroupStacking {})
+    }
+}
+
+impl Display for ToggleGroupStacking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "toggle_group_stacking")
+    }
+}
+
+pub struct FocusNextInStack {}
+
+impl Action for FocusNextInStack {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.focus_next_in_stack();
+    }
+}
+
+impl FromStr for FocusNextInStack {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up = input.to_ascii_uppercase();
+        if !input_up.contains("FOCUS_NEXT_IN_STACK") {
+            return Err(());
+        }
+        Ok(FocusNextInStack {})
+    }
+}
+
+impl Display for FocusNextInStack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "focus_next_in_stack")
+    }
+}
@@ -0,0 +1,34 @@
+use crate::data::action::Action;
+use crate::data::common::event::Event;
+use crate::state::management::state_manager::StateManager;
+use crate::win_api;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Show the keybinds of the config on screen, or hide them again
+pub struct ShowKeybindings {}
+
+impl Action for ShowKeybindings {
+    // The keybinds are owned by the KeyManager, which draws them once the main loop gets the event
+    fn execute(&self, _state_manager: &mut StateManager) {
+        win_api::window::send_event_message(Event::show_keybindings());
+    }
+}
+
+impl FromStr for ShowKeybindings {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.eq("SHOW_KEYBINDINGS") {
+            return Err(());
+        }
+        Ok(ShowKeybindings {})
+    }
+}
+
+impl Display for ShowKeybindings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "show_keybindings")
+    }
+}
@@ -1,3 +1,9 @@
-mod groups;
+pub mod config;
+pub mod delay;
+pub mod exec;
+pub mod groups;
+pub mod keybindings;
+pub mod modes;
+pub mod pass_through;
 pub mod windows;
 pub mod workspaces;
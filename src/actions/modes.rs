@@ -0,0 +1,64 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub struct EnterMode {
+    pub name: String,
+}
+
+impl Action for EnterMode {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.enter_mode(&self.name);
+    }
+}
+
+impl FromStr for EnterMode {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up = input.to_ascii_uppercase();
+        if !input_up.starts_with("ENTER_MODE_") {
+            return Err(());
+        }
+        let name = input_up.strip_prefix("ENTER_MODE_").unwrap();
+        if name.is_empty() {
+            return Err(());
+        }
+        Ok(EnterMode {
+            name: name.to_ascii_lowercase(),
+        })
+    }
+}
+
+impl Display for EnterMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "enter_mode_{}", self.name)
+    }
+}
+
+pub struct ExitMode {}
+
+impl Action for ExitMode {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.exit_mode();
+    }
+}
+
+impl FromStr for ExitMode {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up = input.to_ascii_uppercase();
+        if !input_up.contains("EXIT_MODE") {
+            return Err(());
+        }
+        Ok(ExitMode {})
+    }
+}
+
+impl Display for ExitMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit_mode")
+    }
+}
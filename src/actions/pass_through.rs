@@ -0,0 +1,30 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Lets the keys of a keybind reach the focused application instead of being handled by Fenestra
+pub struct PassThrough {}
+
+impl Action for PassThrough {
+    // The KeyManager leaves pass_through chords to the application, so there's nothing to do here
+    fn execute(&self, _state_manager: &mut StateManager) {}
+}
+
+impl FromStr for PassThrough {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.eq("PASS_THROUGH") {
+            return Err(());
+        }
+        Ok(PassThrough {})
+    }
+}
+
+impl Display for PassThrough {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pass_through")
+    }
+}
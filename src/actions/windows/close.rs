@@ -1,5 +1,6 @@
 use crate::data::action::Action;
 use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 pub struct CloseWindow {}
@@ -21,3 +22,9 @@ impl FromStr for CloseWindow {
         Ok(CloseWindow {})
     }
 }
+
+impl Display for CloseWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "close_window")
+    }
+}
@@ -0,0 +1,42 @@
+use crate::data::action::Action;
+use crate::data::drag::DragKind;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Drag the window under the cursor, bound to a mouse button like 'WIN + LeftDrag'
+pub struct DragWindow {
+    pub kind: DragKind,
+}
+
+impl Action for DragWindow {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.start_drag(self.kind);
+    }
+}
+
+impl FromStr for DragWindow {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        match input_up.as_str() {
+            "DRAG_WINDOW" => Ok(DragWindow {
+                kind: DragKind::MOVE,
+            }),
+            "DRAG_SPLIT" => Ok(DragWindow {
+                kind: DragKind::RESIZE,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for DragWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            DragKind::MOVE => write!(f, "drag_window"),
+            DragKind::RESIZE => write!(f, "drag_split"),
+        }
+    }
+}
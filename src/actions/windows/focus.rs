@@ -1,4 +1,5 @@
 use crate::data::action::Action;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 use crate::data::common::direction::Direction;
@@ -31,3 +32,9 @@ impl FromStr for FocusWindow {
         })
     }
 }
+
+impl Display for FocusWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "focus_window_{}", self.direction)
+    }
+}
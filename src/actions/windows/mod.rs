@@ -1,3 +1,6 @@
 pub mod close;
+pub mod drag;
 pub mod focus;
 pub mod r#move;
+pub mod resize;
+pub mod sticky;
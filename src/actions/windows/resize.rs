@@ -0,0 +1,40 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Share of the group a window gains or loses per resize
+const RESIZE_STEP: f32 = 0.1;
+
+pub struct ResizeWindow {
+    pub delta: f32,
+}
+
+impl Action for ResizeWindow {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.resize_window(self.delta);
+    }
+}
+
+impl FromStr for ResizeWindow {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_uppercase().as_str() {
+            "GROW_WINDOW" => Ok(ResizeWindow { delta: RESIZE_STEP }),
+            "SHRINK_WINDOW" => Ok(ResizeWindow {
+                delta: -RESIZE_STEP,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for ResizeWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.delta > 0.0 {
+            true => write!(f, "grow_window"),
+            false => write!(f, "shrink_window"),
+        }
+    }
+}
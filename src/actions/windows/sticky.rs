@@ -0,0 +1,30 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub struct ToggleSticky {}
+
+impl Action for ToggleSticky {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.toggle_sticky();
+    }
+}
+
+impl FromStr for ToggleSticky {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.contains("TOGGLE_STICKY") {
+            return Err(());
+        }
+        Ok(ToggleSticky {})
+    }
+}
+
+impl Display for ToggleSticky {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "toggle_sticky")
+    }
+}
@@ -1,6 +1,8 @@
 use crate::data::action::Action;
+use crate::data::workspace::workspace_index;
 use crate::state::management::state_manager::StateManager;
 use log::error;
+use std::fmt::{Display, Formatter};
 use std::process::exit;
 use std::str::FromStr;
 
@@ -10,7 +12,7 @@ pub struct FocusWorkspace {
 
 impl Action for FocusWorkspace {
     fn execute(&self, state_manager: &mut StateManager) {
-        state_manager.focus_workspace(self.id - 1);
+        state_manager.focus_workspace(workspace_index(self.id));
     }
 }
 
@@ -32,3 +34,41 @@ impl FromStr for FocusWorkspace {
         })
     }
 }
+
+impl Display for FocusWorkspace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "focus_workspace_{}", self.id)
+    }
+}
+
+// Focus the next or previous workspace of the current monitor, handy for the scroll wheel
+pub struct CycleWorkspace {
+    pub step: i32,
+}
+
+impl Action for CycleWorkspace {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.cycle_workspace(self.step);
+    }
+}
+
+impl FromStr for CycleWorkspace {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        match input_up.as_str() {
+            "FOCUS_NEXT_WORKSPACE" => Ok(CycleWorkspace { step: 1 }),
+            "FOCUS_PREVIOUS_WORKSPACE" => Ok(CycleWorkspace { step: -1 }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for CycleWorkspace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.step > 0 {
+            true => write!(f, "focus_next_workspace"),
+            false => write!(f, "focus_previous_workspace"),
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod focus;
-pub mod r#move;
\ No newline at end of file
+pub mod r#move;
+pub mod scratchpad;
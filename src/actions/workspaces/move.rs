@@ -1,7 +1,9 @@
+use std::fmt::{Display, Formatter};
 use std::process::exit;
 use std::str::FromStr;
 
 use crate::data::action::Action;
+use crate::data::workspace::workspace_index;
 use crate::state::management::state_manager::StateManager;
 use log::error;
 
@@ -11,7 +13,7 @@ pub struct MoveToWorkspace {
 
 impl Action for MoveToWorkspace {
     fn execute(&self, state_manager: &mut StateManager) {
-        state_manager.move_to_workspace(self.id - 1)
+        state_manager.move_to_workspace(workspace_index(self.id))
     }
 }
 
@@ -33,3 +35,9 @@ impl FromStr for MoveToWorkspace {
         })
     }
 }
+
+impl Display for MoveToWorkspace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "send_to_workspace_{}", self.id)
+    }
+}
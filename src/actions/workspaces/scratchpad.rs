@@ -0,0 +1,77 @@
+use crate::data::action::Action;
+use crate::state::management::state_manager::StateManager;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub struct MoveToScratchpad {
+    pub name: Option<String>,
+}
+
+impl Action for MoveToScratchpad {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.move_to_scratchpad(self.name.clone());
+    }
+}
+
+impl FromStr for MoveToScratchpad {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.starts_with("MOVE_TO_SCRATCHPAD") {
+            return Err(());
+        }
+        let name = scratchpad_name(input_up.strip_prefix("MOVE_TO_SCRATCHPAD").unwrap())?;
+        Ok(MoveToScratchpad { name })
+    }
+}
+
+impl Display for MoveToScratchpad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "move_to_scratchpad_{}", name),
+            None => write!(f, "move_to_scratchpad"),
+        }
+    }
+}
+
+pub struct ToggleScratchpad {
+    pub name: Option<String>,
+}
+
+impl Action for ToggleScratchpad {
+    fn execute(&self, state_manager: &mut StateManager) {
+        state_manager.toggle_scratchpad(&self.name);
+    }
+}
+
+impl FromStr for ToggleScratchpad {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input_up: String = input.to_ascii_uppercase();
+        if !input_up.starts_with("TOGGLE_SCRATCHPAD") {
+            return Err(());
+        }
+        let name = scratchpad_name(input_up.strip_prefix("TOGGLE_SCRATCHPAD").unwrap())?;
+        Ok(ToggleScratchpad { name })
+    }
+}
+
+impl Display for ToggleScratchpad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "toggle_scratchpad_{}", name),
+            None => write!(f, "toggle_scratchpad"),
+        }
+    }
+}
+
+// Scratchpad names follow the action identifier, e.g. toggle_scratchpad_terminal
+fn scratchpad_name(suffix: &str) -> Result<Option<String>, ()> {
+    if suffix.is_empty() {
+        return Ok(None);
+    }
+    match suffix.strip_prefix('_') {
+        Some(name) if !name.is_empty() => Ok(Some(name.to_ascii_lowercase())),
+        _ => Err(()),
+    }
+}
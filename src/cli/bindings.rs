@@ -0,0 +1,32 @@
+use std::str::FromStr;
+
+use crate::cli::{config_path, flag_values, machine, print_errors, FORMAT_FLAG};
+use crate::config;
+use crate::config::bindings::BindingsFormat;
+
+/*
+   'bindings [config] [--format text|markdown|json] [--hostname <name>] [--monitor <name>]...'
+   prints the keybinds of the config as Fenestra resolves them, for a cheat sheet or other tools
+*/
+pub fn bindings(args: &[String]) -> i32 {
+    let format = match flag_values(args, FORMAT_FLAG).last() {
+        Some(format) => match BindingsFormat::from_str(format) {
+            Ok(format) => format,
+            Err(message) => {
+                eprintln!("{}", message);
+                return 1;
+            }
+        },
+        None => BindingsFormat::TEXT,
+    };
+    let config_path = config_path(args);
+    let config = match config::parse::parse_content(&config_path, &machine(args)) {
+        Ok(config) => config,
+        Err(errors) => {
+            print_errors(&errors);
+            return 1;
+        }
+    };
+    print!("{}", config::bindings::render(&config.keybinds, format));
+    return 0;
+}
@@ -0,0 +1,38 @@
+use crate::cli::{config_path, has_flag, machine, print_errors};
+use crate::config;
+
+// Warnings fail the check as well
+const STRICT_FLAG: &str = "--strict";
+
+/*
+   'check [config] [--strict] [--hostname <name>] [--monitor <name>]...' parses the config like
+   Fenestra does at startup, without setting hooks or touching any window, and lists the keybinds
+   it ends up with. Exits with 1 when the config has errors
+*/
+pub fn check(args: &[String]) -> i32 {
+    let config_path = config_path(args);
+    let config = match config::parse::parse_content(&config_path, &machine(args)) {
+        Ok(config) => config,
+        Err(errors) => {
+            print_errors(&errors);
+            eprintln!("{} has {} error(s)", config_path.display(), errors.len());
+            return 1;
+        }
+    };
+    for warning in config.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+    println!(
+        "{} is valid, with {} keybind(s) and {} warning(s)",
+        config_path.display(),
+        config.keybinds.len(),
+        config.warnings.len()
+    );
+    for line in config::bindings::lines(&config.keybinds) {
+        println!("  {}", line);
+    }
+    if has_flag(args, STRICT_FLAG) && !config.warnings.is_empty() {
+        return 1;
+    }
+    return 0;
+}
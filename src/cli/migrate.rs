@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::{config_path, machine, positional, print_errors};
+use crate::config;
+
+// 'migrate-config [input] [output]', the input defaults to the config in use
+pub fn migrate_config(args: &[String]) -> i32 {
+    let input = config_path(args);
+    let output = match positional(args).get(1) {
+        Some(output) => PathBuf::from(output),
+        None => input.with_extension("toml"),
+    };
+    if config::parse::is_toml(&input) {
+        eprintln!("{} is already a TOML config", input.display());
+        return 1;
+    }
+    if output.exists() {
+        eprintln!("{} already exists, not overwriting it", output.display());
+        return 1;
+    }
+    let content = match fs::read_to_string(&input) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", input.display(), error);
+            return 1;
+        }
+    };
+    let migrated = match config::migrate::to_toml(&input, &content, &machine(args)) {
+        Ok(migrated) => migrated,
+        Err(errors) => {
+            print_errors(&errors);
+            return 1;
+        }
+    };
+    if let Err(error) = fs::write(&output, migrated) {
+        eprintln!("Failed to write {}: {}", output.display(), error);
+        return 1;
+    }
+    println!("Migrated {} to {}", input.display(), output.display());
+    return 0;
+}
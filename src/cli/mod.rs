@@ -0,0 +1,112 @@
+mod bindings;
+mod check;
+mod migrate;
+
+use std::path::PathBuf;
+
+use crate::config;
+use crate::config::lexer::ConfigError;
+use crate::config::profile::Machine;
+use crate::state::init;
+use crate::win_api;
+
+const BINDINGS: &str = "bindings";
+const CHECK: &str = "check";
+const MIGRATE_CONFIG: &str = "migrate-config";
+// Flags taking a value, given as '--flag <value>' or '--flag=<value>'
+const CONFIG_FLAG: &str = "--config";
+const FORMAT_FLAG: &str = "--format";
+const HOSTNAME_FLAG: &str = "--hostname";
+const MONITOR_FLAG: &str = "--monitor";
+const VALUE_FLAGS: &[&str] = &[CONFIG_FLAG, FORMAT_FLAG, HOSTNAME_FLAG, MONITOR_FLAG];
+
+/*
+   Run the command given on the command line, if any. Returns the exit code of the command, or
+   None when fenestra should start as usual
+*/
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+    let args = &args[1..];
+    if ![BINDINGS, CHECK, MIGRATE_CONFIG].contains(&command.as_str()) {
+        return None;
+    }
+    win_api::misc::attach_console();
+    let exit_code = match command.as_str() {
+        BINDINGS => bindings::bindings(args),
+        CHECK => check::check(args),
+        _ => migrate::migrate_config(args),
+    };
+    return Some(exit_code);
+}
+
+// The config given as the first argument, or the one fenestra would use
+fn config_path(args: &[String]) -> PathBuf {
+    return match positional(args).first() {
+        Some(config_path) => PathBuf::from(config_path),
+        None => config::load::locate(args),
+    };
+}
+
+/*
+   The machine conditional blocks are checked against. --hostname and --monitor stand in for the
+   current machine, so the profile of another machine can be checked
+*/
+fn machine(args: &[String]) -> Machine {
+    let mut machine = match cfg!(windows) {
+        true => init::machine(),
+        false => Machine::new(Vec::new()),
+    };
+    if let Some(hostname) = flag_values(args, HOSTNAME_FLAG).last() {
+        machine.hostname = hostname.to_string();
+    }
+    let monitors = flag_values(args, MONITOR_FLAG);
+    if !monitors.is_empty() {
+        machine.monitors = monitors.iter().map(|monitor| monitor.to_string()).collect();
+    }
+    return machine;
+}
+
+fn print_errors(errors: &[ConfigError]) {
+    for error in errors {
+        eprintln!("error: {}", error);
+    }
+}
+
+// Arguments which aren't flags, skipping the values of flags
+fn positional(args: &[String]) -> Vec<&String> {
+    let mut positional: Vec<&String> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            args.next();
+            continue;
+        }
+        if !arg.starts_with("--") {
+            positional.push(arg);
+        }
+    }
+    return positional;
+}
+
+// Every value given for the flag, in order
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    let mut values: Vec<&str> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            if let Some(value) = args.next() {
+                values.push(value);
+            }
+        } else if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            values.push(value);
+        }
+    }
+    return values;
+}
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    return args.iter().any(|arg| arg == flag);
+}
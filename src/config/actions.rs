@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::config::lexer::split_unquoted;
+use crate::data::action::WindowManagerAction;
+
+const ACTION_SEPARATOR: char = ';';
+// Macros are defined as 'macro <name>: <actions>' and referenced as macro_<name>
+pub const MACRO_DEFINITION: &str = "macro ";
+const MACRO_PREFIX: &str = "MACRO_";
+
+// Parse a ';' separated list of actions, expanding the macros it references
+pub fn parse_actions(
+    actions: &str,
+    macros: &HashMap<String, String>,
+) -> Result<Vec<WindowManagerAction>, String> {
+    return expand(actions, macros, &mut Vec::new());
+}
+
+fn expand(
+    actions: &str,
+    macros: &HashMap<String, String>,
+    expanding: &mut Vec<String>,
+) -> Result<Vec<WindowManagerAction>, String> {
+    let mut parsed: Vec<WindowManagerAction> = Vec::new();
+    /*
+       Every unquoted separator ends an action, the command line of an exec included. Quoted parts,
+       like the arguments of an exec command line, may contain the separator
+    */
+    for action in split_unquoted(actions, ACTION_SEPARATOR, 1)
+        .iter()
+        .map(|action| action.text.as_str())
+        .filter(|action| !action.is_empty())
+    {
+        let action_up = action.to_ascii_uppercase();
+        let name = match action_up.strip_prefix(MACRO_PREFIX) {
+            Some(name) => name.to_ascii_lowercase(),
+            None => {
+                let parsed_action = WindowManagerAction::from_str(action)
+                    .map_err(|_| format!("Invalid action name {}", action))?;
+                parsed.push(parsed_action);
+                continue;
+            }
+        };
+        let definition = macros
+            .get(&name)
+            .ok_or_else(|| format!("Undefined macro {}", name))?;
+        if let Some(start) = expanding.iter().position(|expanded| *expanded == name) {
+            if start == expanding.len() - 1 {
+                return Err(format!("Macro {} references itself", name));
+            }
+            let cycle: Vec<&str> = expanding[start..]
+                .iter()
+                .chain([&name])
+                .map(String::as_str)
+                .collect();
+            return Err(format!(
+                "Macros reference each other in a cycle: {}",
+                cycle.join(" -> ")
+            ));
+        }
+        expanding.push(name);
+        parsed.extend(expand(definition, macros, expanding)?);
+        expanding.pop();
+    }
+    if parsed.is_empty() {
+        return Err(String::from("Missing action"));
+    }
+    return Ok(parsed);
+}
+
+// Returns the macro name when the identifier defines a macro
+pub fn macro_name(identifier: &str) -> Option<String> {
+    let prefix = identifier.get(..MACRO_DEFINITION.len())?;
+    if !prefix.eq_ignore_ascii_case(MACRO_DEFINITION) {
+        return None;
+    }
+    let name = identifier[MACRO_DEFINITION.len()..].trim();
+    if name.is_empty() {
+        return None;
+    }
+    return Some(name.to_ascii_lowercase());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::config::actions::{macro_name, parse_actions};
+
+    fn macros(definitions: &[(&str, &str)]) -> HashMap<String, String> {
+        return definitions
+            .iter()
+            .map(|(name, actions)| (name.to_string(), actions.to_string()))
+            .collect();
+    }
+
+    fn parsed(actions: &str, macros: &HashMap<String, String>) -> Result<Vec<String>, String> {
+        return parse_actions(actions, macros)
+            .map(|actions| actions.iter().map(|action| action.to_string()).collect());
+    }
+
+    #[test]
+    fn action_lists_run_in_order() {
+        assert_eq!(
+            parsed("send_to_workspace_3; focus_workspace_3", &HashMap::new()),
+            Ok(vec![
+                String::from("send_to_workspace_3"),
+                String::from("focus_workspace_3")
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_macros_expand_in_place() {
+        let macros = macros(&[
+            ("inner", "delay_500; focus_workspace_2"),
+            ("outer", "exec: wt.exe; macro_inner"),
+        ]);
+        assert_eq!(
+            parsed("MACRO_Outer; grow_window", &macros),
+            Ok(vec![
+                String::from("exec: wt.exe"),
+                String::from("delay_500"),
+                String::from("focus_workspace_2"),
+                String::from("grow_window"),
+            ])
+        );
+        // The same macro may be used twice, as long as it isn't inside itself
+        assert_eq!(
+            parsed("macro_inner; macro_inner", &macros).unwrap().len(),
+            4
+        );
+    }
+
+    #[test]
+    fn cycles_are_errors() {
+        let macros = macros(&[
+            ("self", "grow_window; macro_self"),
+            ("a", "macro_b"),
+            ("b", "close_window; macro_c"),
+            ("c", "macro_a"),
+        ]);
+        assert_eq!(
+            parsed("macro_self", &macros),
+            Err(String::from("Macro self references itself"))
+        );
+        assert_eq!(
+            parsed("macro_a", &macros),
+            Err(String::from(
+                "Macros reference each other in a cycle: a -> b -> c -> a"
+            ))
+        );
+    }
+
+    #[test]
+    fn undefined_macros_and_actions_are_errors() {
+        let macros = macros(&[("uses_missing", "grow_window; macro_missing")]);
+        assert_eq!(
+            parsed("macro_uses_missing", &macros),
+            Err(String::from("Undefined macro missing"))
+        );
+        assert_eq!(
+            parsed("grow_window; no_such_action", &macros),
+            Err(String::from("Invalid action name no_such_action"))
+        );
+    }
+
+    #[test]
+    fn empty_lists_are_errors() {
+        let macros = macros(&[("empty", " ; ")]);
+        for actions in ["", " ; ;", "macro_empty"] {
+            assert_eq!(
+                parsed(actions, &macros),
+                Err(String::from("Missing action"))
+            );
+        }
+        // Empty parts between actions are skipped
+        assert_eq!(parsed("grow_window;; ", &macros).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn semicolons_end_exec_command_lines_unless_quoted() {
+        assert_eq!(
+            parsed("exec: wt.exe; delay_500", &HashMap::new()),
+            Ok(vec![
+                String::from("exec: wt.exe"),
+                String::from("delay_500")
+            ])
+        );
+        assert_eq!(
+            parsed("exec: pwsh -c \"Get-Date; pause\"", &HashMap::new()),
+            Ok(vec![String::from("exec: pwsh -c \"Get-Date; pause\"")])
+        );
+    }
+
+    #[test]
+    fn macro_definitions_are_named_after_the_keyword() {
+        assert_eq!(macro_name("macro dev"), Some(String::from("dev")));
+        assert_eq!(macro_name("MACRO  Dev "), Some(String::from("dev")));
+        assert_eq!(macro_name("macro "), None);
+        assert_eq!(macro_name("macro_dev"), None);
+    }
+}
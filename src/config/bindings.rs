@@ -0,0 +1,271 @@
+use std::str::FromStr;
+
+use crate::data::key::Keybind;
+
+const HEADER: [&str; 5] = ["Mode", "Keys", "Actions", "Options", "Source"];
+const GLOBAL_MODE: &str = "global";
+
+pub enum BindingsFormat {
+    TEXT,
+    MARKDOWN,
+    JSON,
+}
+
+impl FromStr for BindingsFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        return match input.to_ascii_lowercase().as_str() {
+            "text" => Ok(BindingsFormat::TEXT),
+            "markdown" | "md" => Ok(BindingsFormat::MARKDOWN),
+            "json" => Ok(BindingsFormat::JSON),
+            _ => Err(format!(
+                "Unknown format {}, expected text, markdown or json",
+                input
+            )),
+        };
+    }
+}
+
+/*
+   The keybinds as Fenestra ends up with them, with variables expanded, macros replaced by their
+   actions and the keybinds Fenestra adds itself included. Global keybinds come first, followed by
+   those of each binding mode, each group in the order of the config
+*/
+pub fn render(keybinds: &[Keybind], format: BindingsFormat) -> String {
+    let mut keybinds: Vec<&Keybind> = keybinds.iter().collect();
+    keybinds.sort_by(|a, b| a.mode.cmp(&b.mode));
+    return match format {
+        BindingsFormat::TEXT => text(&keybinds),
+        BindingsFormat::MARKDOWN => markdown(&keybinds),
+        BindingsFormat::JSON => json(&keybinds),
+    };
+}
+
+// The text lines of the table, also drawn by the show_keybindings overlay
+pub fn lines(keybinds: &[Keybind]) -> Vec<String> {
+    return render(keybinds, BindingsFormat::TEXT)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+}
+
+fn row(keybind: &Keybind) -> [String; 5] {
+    return [
+        keybind
+            .mode
+            .clone()
+            .unwrap_or_else(|| GLOBAL_MODE.to_string()),
+        keybind.sequence_string(),
+        actions(keybind).join("; "),
+        keybind.options(),
+        keybind.source(),
+    ];
+}
+
+fn actions(keybind: &Keybind) -> Vec<String> {
+    return keybind
+        .actions
+        .iter()
+        .map(|action| action.to_string())
+        .collect();
+}
+
+// Columns padded to their widest cell, the last one left as it is
+fn text(keybinds: &[&Keybind]) -> String {
+    let rows: Vec<[String; 5]> = [HEADER.map(String::from)]
+        .into_iter()
+        .chain(keybinds.iter().map(|keybind| row(keybind)))
+        .collect();
+    let widths: Vec<usize> = (0..HEADER.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut text = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        text.push_str(cells.join("  ").trim_end());
+        text.push('\n');
+    }
+    return text;
+}
+
+fn markdown(keybinds: &[&Keybind]) -> String {
+    let mut markdown = format!(
+        "| {} |\n|{}|\n",
+        HEADER.join(" | "),
+        vec!["---"; HEADER.len()].join("|")
+    );
+    for keybind in keybinds {
+        let cells: Vec<String> = row(keybind)
+            .iter()
+            .map(|cell| match cell.is_empty() {
+                true => String::from(" "),
+                false => code_span(cell),
+            })
+            .collect();
+        markdown.push_str(&format!("|{}|\n", cells.join("|")));
+    }
+    return markdown;
+}
+
+/*
+   Cells are code spans, with '|' escaped for the table. A span containing backticks is delimited by
+   a longer run of backticks than it contains, padded with spaces when it starts or ends with one
+*/
+fn code_span(cell: &str) -> String {
+    let longest_run = cell
+        .split(|character| character != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    let delimiter = "`".repeat(longest_run + 1);
+    let padding = match cell.starts_with('`') || cell.ends_with('`') {
+        true => " ",
+        false => "",
+    };
+    return format!(
+        " {}{}{}{}{} ",
+        delimiter,
+        padding,
+        cell.replace('|', "\\|"),
+        padding,
+        delimiter
+    );
+}
+
+// An array of keybinds, global keybinds have a null mode and line 0 marks those Fenestra added
+fn json(keybinds: &[&Keybind]) -> String {
+    let entries: Vec<String> = keybinds
+        .iter()
+        .map(|keybind| {
+            let mode = match &keybind.mode {
+                Some(mode) => json_string(mode),
+                None => String::from("null"),
+            };
+            let actions: Vec<String> = actions(keybind)
+                .iter()
+                .map(|action| json_string(action))
+                .collect();
+            format!(
+                "  {{\"mode\": {}, \"keys\": {}, \"actions\": [{}], \"options\": {}, \"file\": {}, \"line\": {}}}",
+                mode,
+                json_string(&keybind.sequence_string()),
+                actions.join(", "),
+                json_string(&keybind.options()),
+                json_string(&keybind.file.display().to_string()),
+                keybind.line
+            )
+        })
+        .collect();
+    if entries.is_empty() {
+        return String::from("[]\n");
+    }
+    return format!("[\n{}\n]\n", entries.join(",\n"));
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::str::FromStr;
+
+    use crate::config::bindings::{lines, render, BindingsFormat};
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+    use crate::data::key::Keybind;
+
+    fn keybinds(content: &str) -> Vec<Keybind> {
+        let machine = Machine::default();
+        return parse_str(Path::new("fenestra.conf"), content, &machine)
+            .unwrap()
+            .keybinds;
+    }
+
+    #[test]
+    fn keys_are_written_modifiers_first() {
+        let keybinds = keybinds("close_window: q + SHIFT + WIN\nfocus_workspace_1: RALT + F1\n");
+        assert_eq!(keybinds[0].sequence_string(), "WIN + SHIFT + Q");
+        assert_eq!(keybinds[1].sequence_string(), "RALT + F1");
+    }
+
+    #[test]
+    fn text_lists_global_keybinds_before_modes() {
+        let keybinds = keybinds(
+            "resize.grow_window: l\nenter_mode_resize: WIN + r\nresize.exit_mode: ESCAPE\n",
+        );
+        assert_eq!(
+            lines(&keybinds),
+            vec![
+                "Mode    Keys     Actions            Options  Source",
+                "global  WIN + R  enter_mode_resize           fenestra.conf:2",
+                "resize  L        grow_window                 fenestra.conf:1",
+                "resize  ESCAPE   exit_mode                   fenestra.conf:3",
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_and_backticks() {
+        let keybinds =
+            keybinds("pass_through: WIN + q [title=\"a | b\"]\n\"exec: echo `date`\": WIN + e\n");
+        let markdown = render(&keybinds, BindingsFormat::MARKDOWN);
+        let rows: Vec<&str> = markdown.lines().collect();
+        assert_eq!(rows[0], "| Mode | Keys | Actions | Options | Source |");
+        assert_eq!(
+            rows[2],
+            "| `global` | `WIN + Q` | `pass_through` | `title=\"a \\| b\"` | `fenestra.conf:1` |"
+        );
+        assert_eq!(
+            rows[3],
+            "| `global` | `WIN + E` | `` exec: echo `date` `` | | `fenestra.conf:2` |"
+        );
+    }
+
+    #[test]
+    fn json_escapes_strings_and_marks_added_keybinds() {
+        let keybinds = keybinds(
+            "\"exec: C:\\dir\\app.exe\": WIN + e [title=\"a b\"]\nresize.grow_window: l\n",
+        );
+        assert_eq!(
+            render(&keybinds, BindingsFormat::JSON),
+            "[\n  {\"mode\": null, \"keys\": \"WIN + E\", \"actions\": [\"exec: C:\\\\dir\\\\app.exe\"], \"options\": \"title=\\\"a b\\\"\", \"file\": \"fenestra.conf\", \"line\": 1},\n  {\"mode\": \"resize\", \"keys\": \"L\", \"actions\": [\"grow_window\"], \"options\": \"\", \"file\": \"fenestra.conf\", \"line\": 2},\n  {\"mode\": \"resize\", \"keys\": \"ESCAPE\", \"actions\": [\"exit_mode\"], \"options\": \"\", \"file\": \"\", \"line\": 0}\n]\n"
+        );
+        assert_eq!(render(&[], BindingsFormat::JSON), "[]\n");
+    }
+
+    #[test]
+    fn formats_are_parsed_ignoring_case() {
+        assert!(matches!(
+            BindingsFormat::from_str("MD"),
+            Ok(BindingsFormat::MARKDOWN)
+        ));
+        assert!(BindingsFormat::from_str("xml").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use log::info;
+
+use crate::config::lexer::ConfigError;
+use crate::data::key::{generic_key, key_code, Keybind};
+
+// Shortcuts Windows handles before Fenestra, listed in WinBinding.md
+const OS_SHORTCUTS: &[(&str, &str)] = &[
+    ("WIN + R", "Run dialog"),
+    ("WIN + E", "File Explorer"),
+    ("WIN + N", "Notifications panel"),
+    ("WIN + K", "Casting panel"),
+    ("WIN + W", "Widgets panel"),
+    ("WIN + U", "Accessibility panel"),
+    ("WIN + H", "Speech to text"),
+    ("WIN + D", "Show desktop"),
+    ("WIN + A", "Quick settings"),
+    ("WIN + S", "Search"),
+    ("WIN + I", "Settings"),
+    ("WIN + X", "Quick link menu"),
+    ("WIN + V", "Clipboard history"),
+    ("WIN + P", "Projection settings"),
+    ("WIN + TAB", "Task view"),
+];
+const KERNEL_SHORTCUTS: &[(&str, &str)] = &[
+    ("WIN + L", "Lock the desktop"),
+    ("WIN + G", "Xbox GameBar"),
+    ("CTRL + ALT + DELETE", "Security options"),
+];
+
+/*
+   Report keybinds which shadow each other or Windows shortcuts. Keybinds using the exact same keys
+   in the same binding mode and for the same applications would all run at once, which is an error.
+   A keybind from a later file, like the overrides of a machine including a shared config, or from
+   a conditional block that applies replaces the earlier one instead. Returns the errors, followed
+   by the warnings which don't stop the config from being used
+*/
+pub fn check_conflicts(keybinds: &mut Vec<Keybind>) -> (Vec<ConfigError>, Vec<ConfigError>) {
+    let mut errors: Vec<ConfigError> = Vec::new();
+    let mut warnings: Vec<ConfigError> = Vec::new();
+    let mut duplicates: Vec<usize> = Vec::new();
+    for (index, keybind) in keybinds.iter().enumerate() {
+        for (earlier_index, earlier) in keybinds[..index].iter().enumerate() {
+            if earlier.mode != keybind.mode
+                || duplicates.contains(&index)
+                || duplicates.contains(&earlier_index)
+            {
+                continue;
+            }
+            let sequence = codes(keybind);
+            let earlier_sequence = codes(earlier);
+            let same_sequence = sequences_overlap(&sequence, &earlier_sequence);
+            let same_keys = same_sequence && earlier.rule == keybind.rule;
+            if same_keys
+                && keybind.line != 0
+                && (keybind.file != earlier.file || keybind.conditional)
+            {
+                info!(
+                    "Keybind at {} overrides the keybind at {} ({})",
+                    keybind.source(),
+                    earlier.source(),
+                    keybind.sequence_string()
+                );
+                duplicates.push(earlier_index);
+            } else if same_keys {
+                errors.push(warning(
+                    keybind,
+                    format!(
+                        "Keybind uses the same keys ({}) as the keybind at {} ({})",
+                        keybind.sequence_string(),
+                        earlier.source(),
+                        earlier.sequence_string()
+                    ),
+                ));
+                duplicates.push(index);
+            } else if same_sequence {
+                // An application keybind overrides the global one, there's nothing to report
+                continue;
+            } else if is_prefix(&sequence, &earlier_sequence) {
+                warnings.push(prefix_warning(keybind, earlier));
+            } else if is_prefix(&earlier_sequence, &sequence) {
+                warnings.push(prefix_warning(earlier, keybind));
+            }
+        }
+    }
+    let mut index = 0;
+    keybinds.retain(|_| {
+        index += 1;
+        !duplicates.contains(&(index - 1))
+    });
+
+    for keybind in keybinds.iter().filter(|keybind| keybind.mode.is_none()) {
+        let first_chord = generic_chord(&codes(keybind)[0]);
+        for (shortcut, description) in KERNEL_SHORTCUTS {
+            if shortcut_chord(shortcut) == first_chord {
+                errors.push(warning(
+                    keybind,
+                    format!(
+                        "Keybind uses the kernel level shortcut {} ({}) from WinBinding.md, it will never run",
+                        shortcut, description
+                    ),
+                ));
+            }
+        }
+        for (shortcut, description) in OS_SHORTCUTS {
+            if shortcut_chord(shortcut) == first_chord {
+                warnings.push(warning(
+                    keybind,
+                    format!(
+                        "Keybind overrides the Windows shortcut {} ({}) from WinBinding.md",
+                        shortcut, description
+                    ),
+                ));
+            }
+        }
+    }
+    return (errors, warnings);
+}
+
+// Errors and warnings point at the keybind they are about
+pub fn warning(keybind: &Keybind, message: String) -> ConfigError {
+    return ConfigError::new(&keybind.file, keybind.line, 0, message);
+}
+
+fn prefix_warning(prefix: &Keybind, sequence: &Keybind) -> ConfigError {
+    return warning(
+        prefix,
+        format!(
+            "Keybind ({}) is the start of the sequence at {} ({}), it only runs once the sequence times out",
+            prefix.sequence_string(),
+            sequence.source(),
+            sequence.sequence_string()
+        ),
+    );
+}
+
+fn codes(keybind: &Keybind) -> Vec<Vec<i32>> {
+    return keybind
+        .sequence
+        .iter()
+        .map(|chord| chord.iter().map(|key| key.code).collect())
+        .collect();
+}
+
+fn is_prefix(prefix: &[Vec<i32>], sequence: &[Vec<i32>]) -> bool {
+    return prefix.len() < sequence.len() && sequences_overlap(prefix, &sequence[..prefix.len()]);
+}
+
+fn sequences_overlap(sequence: &[Vec<i32>], other: &[Vec<i32>]) -> bool {
+    return sequence.len() == other.len()
+        && sequence
+            .iter()
+            .zip(other.iter())
+            .all(|(chord, other_chord)| chords_overlap(chord, other_chord));
+}
+
+/*
+   Chords overlap when some key press completes both of them. A generic modifier such as WIN
+   matches either side, so it overlaps LWIN and RWIN, while LWIN and RWIN don't overlap each other
+*/
+fn chords_overlap(chord: &[i32], other: &[i32]) -> bool {
+    if chord.len() != other.len() || generic_chord(chord) != generic_chord(other) {
+        return false;
+    }
+    let by_generic_key = |chord: &[i32]| {
+        let mut keys: Vec<(i32, i32)> = chord
+            .iter()
+            .map(|code| (generic_key(*code).unwrap_or(*code), *code))
+            .collect();
+        keys.sort();
+        return keys;
+    };
+    return by_generic_key(chord)
+        .iter()
+        .zip(by_generic_key(other).iter())
+        .all(|((generic, code), (_, other_code))| {
+            code == other_code || code == generic || other_code == generic
+        });
+}
+
+// Left and right hand modifiers collide with Windows shortcuts just like the generic ones
+fn generic_chord(chord: &[i32]) -> Vec<i32> {
+    let mut chord: Vec<i32> = chord
+        .iter()
+        .map(|code| generic_key(*code).unwrap_or(*code))
+        .collect();
+    chord.sort();
+    return chord;
+}
+
+fn shortcut_chord(shortcut: &str) -> Vec<i32> {
+    let chord: Vec<i32> = shortcut.split('+').filter_map(key_code).collect();
+    return generic_chord(&chord);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::lexer::ConfigError;
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+
+    fn errors(content: &str) -> Vec<ConfigError> {
+        let machine = Machine::default();
+        return parse_str(Path::new("fenestra.conf"), content, &machine)
+            .err()
+            .unwrap_or_default();
+    }
+
+    #[test]
+    fn generic_modifiers_collide_with_sided_ones() {
+        let errors = errors("close_window: WIN + CTRL + q\nfocus_workspace_1: LWIN + CTRL + q\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 2);
+        let errors = self::errors("close_window: SHIFT + q\nfocus_workspace_1: RSHIFT + q\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn left_and_right_modifiers_dont_collide() {
+        assert!(errors("close_window: LWIN + q\nfocus_workspace_1: RWIN + q\n").is_empty());
+    }
+
+    #[test]
+    fn kernel_level_shortcuts_are_errors() {
+        let errors = errors("close_window: LWIN + l\n");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("WIN + L"));
+    }
+}
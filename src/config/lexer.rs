@@ -0,0 +1,461 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+const COMMENT: char = '#';
+pub const QUOTE: char = '"';
+const DEFINITION_SEPARATOR: char = ':';
+// Variables are declared as 'set $name = keys'
+const VARIABLE_DECLARATION: &str = "set";
+pub const VARIABLE_PREFIX: char = '$';
+const VARIABLE_ASSIGNMENT: char = '=';
+// Sections are started by a '[name]' line
+const SECTION_START: char = '[';
+const SECTION_END: char = ']';
+// Other files are read in place of an 'include <path>' line
+const INCLUDE_DIRECTIVE: &str = "include";
+// Lines between 'if <condition> = <value>' and 'end' only apply when the condition holds
+const CONDITION_START: &str = "if";
+const CONDITION_END: &str = "end";
+const CONDITION_ASSIGNMENT: char = '=';
+
+// A problem found in the config, pointing at the file, line and column it was found at
+#[derive(Clone, Debug)]
+pub struct ConfigError {
+    pub file: PathBuf,
+    // Lines and columns count from 1, 0 when the error isn't tied to a line or column
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(file: &Path, line: usize, column: usize, message: impl Into<String>) -> Self {
+        ConfigError {
+            file: file.to_path_buf(),
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}: {}", self.file.display(), self.message);
+        }
+        if self.column == 0 {
+            return write!(f, "{}:{}: {}", self.file.display(), self.line, self.message);
+        }
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DefinitionKind {
+    // 'identifier: value'
+    ENTRY,
+    // 'set $name = value', the identifier holds the name without the '$'
+    VARIABLE,
+    // '[name]', the identifier holds the name without the brackets
+    SECTION,
+    // 'include <path>', the identifier holds the path without quotes
+    INCLUDE,
+    // 'if <condition> = <value>', the identifier holds the condition
+    CONDITION,
+    // 'end', closing the last condition
+    END,
+}
+
+// A single config line, with its comment and surrounding whitespace removed
+#[derive(Clone, Debug)]
+pub struct Definition {
+    pub kind: DefinitionKind,
+    // File the definition was read from, which differs from the config file for included files
+    pub file: PathBuf,
+    // Name of the section the definition is in, empty before the first section
+    pub section: String,
+    pub identifier: String,
+    pub value: String,
+    pub line: usize,
+    pub identifier_column: usize,
+    pub value_column: usize,
+    // Set for definitions inside a conditional block that applies, these override earlier ones
+    pub conditional: bool,
+}
+
+// Part of a value along with the column it starts at
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub column: usize,
+}
+
+impl Token {
+    pub fn new(text: &str, column: usize) -> Self {
+        Token {
+            text: text.to_string(),
+            column,
+        }
+    }
+}
+
+/*
+   Split the config into its definitions. Both CRLF and LF line endings are accepted, blank lines
+   are skipped and comments run from a '#' to the end of the line. Every malformed line is
+   reported, so a single pass lists all of them
+*/
+pub fn tokenize(file: &Path, content: &str) -> (Vec<Definition>, Vec<ConfigError>) {
+    let mut definitions: Vec<Definition> = Vec::new();
+    let mut errors: Vec<ConfigError> = Vec::new();
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut section = String::new();
+    for (index, line) in content.lines().enumerate() {
+        match tokenize_line(line, index + 1) {
+            Ok(Some(mut definition)) => {
+                if definition.kind == DefinitionKind::SECTION {
+                    section = definition.identifier.clone();
+                }
+                definition.file = file.to_path_buf();
+                definition.section = section.clone();
+                definitions.push(definition)
+            }
+            Ok(None) => {}
+            Err((column, message)) => {
+                errors.push(ConfigError::new(file, index + 1, column, message))
+            }
+        }
+    }
+    return (definitions, errors);
+}
+
+fn tokenize_line(line: &str, line_number: usize) -> Result<Option<Definition>, (usize, String)> {
+    let line = strip_comment(line)?;
+    let start = line.len() - line.trim_start().len();
+    let text = line.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let identifier_column = column(line, start);
+    if let Some(header) = text.strip_prefix(SECTION_START) {
+        return tokenize_section(header, identifier_column, line_number).map(Some);
+    }
+    if let Some(declaration) = strip_keyword(text, VARIABLE_DECLARATION) {
+        let declaration_start = start + text.len() - declaration.len();
+        return tokenize_variable(line, declaration_start, line_number).map(Some);
+    }
+    if let Some(path) = strip_keyword(text, INCLUDE_DIRECTIVE) {
+        let path_start = start + text.len() - path.trim_start().len();
+        return Ok(Some(directive(
+            DefinitionKind::INCLUDE,
+            unquote(path),
+            line_number,
+            column(line, path_start),
+        )));
+    }
+    if let Some(condition) = strip_keyword(text, CONDITION_START) {
+        let condition_start = start + text.len() - condition.trim_start().len();
+        return tokenize_condition(line, condition_start, line_number).map(Some);
+    }
+    if text.eq_ignore_ascii_case(CONDITION_END) {
+        return Ok(Some(directive(
+            DefinitionKind::END,
+            "",
+            line_number,
+            identifier_column,
+        )));
+    }
+    // Identifiers containing colons of their own may be wrapped in quotes
+    let (identifier, rest_start) = match text.strip_prefix(QUOTE) {
+        Some(quoted) => match quoted.find(QUOTE) {
+            Some(end) => (quoted[..end].trim(), start + end + 2),
+            None => return Err((identifier_column, String::from("Unterminated quote"))),
+        },
+        None => match identifier_end(text) {
+            Some(end) => (text[..end].trim(), start + end),
+            None => {
+                return Err((
+                    identifier_column,
+                    String::from("Expected 'identifier: value'"),
+                ))
+            }
+        },
+    };
+    let rest = &line[rest_start..];
+    let after_identifier = rest_start + rest.len() - rest.trim_start().len();
+    let value = match rest.trim_start().strip_prefix(DEFINITION_SEPARATOR) {
+        Some(value) => value,
+        None => {
+            return Err((
+                column(line, after_identifier),
+                String::from("Expected ':' after the identifier"),
+            ))
+        }
+    };
+    if identifier.is_empty() {
+        return Err((identifier_column, String::from("Missing identifier")));
+    }
+    let value_start = line.len() - value.trim_start().len();
+    return Ok(Some(Definition {
+        kind: DefinitionKind::ENTRY,
+        file: PathBuf::new(),
+        section: String::new(),
+        identifier: identifier.to_string(),
+        value: value.trim().to_string(),
+        line: line_number,
+        identifier_column,
+        value_column: column(line, value_start),
+        conditional: false,
+    }));
+}
+
+/*
+   An unquoted identifier ends at the first ':', unless it runs an exec action. Its command line may
+   contain colons of its own, so it ends at the last ':' outside of quotes and keybind options
+*/
+fn identifier_end(text: &str) -> Option<usize> {
+    let first = text.find(DEFINITION_SEPARATOR)?;
+    if !runs_exec(&text[..first]) {
+        return Some(first);
+    }
+    let mut last = first;
+    let mut quoted = false;
+    let mut options = false;
+    for (index, char) in text.char_indices() {
+        match char {
+            QUOTE => quoted = !quoted,
+            '[' if !quoted => options = true,
+            ']' if !quoted => options = false,
+            DEFINITION_SEPARATOR if !quoted && !options => last = index,
+            _ => {}
+        }
+    }
+    return Some(last);
+}
+
+// Whether the last action before the first ':' is an exec, optionally prefixed by a binding mode
+fn runs_exec(identifier: &str) -> bool {
+    let action = identifier.rsplit(';').next().unwrap_or("").trim();
+    let action = match action.split_once('.') {
+        Some((mode, action))
+            if mode
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '_') =>
+        {
+            action
+        }
+        _ => action,
+    };
+    let action = action.trim_start().to_ascii_lowercase();
+    return action == "exec" || action.starts_with("exec(") || action.starts_with("exec ");
+}
+
+fn tokenize_variable(
+    line: &str,
+    start: usize,
+    line_number: usize,
+) -> Result<Definition, (usize, String)> {
+    let declaration = &line[start..];
+    let name_start = start + declaration.len() - declaration.trim_start().len();
+    let expected = || String::from("Expected 'set $name = keys'");
+    let name = match line[name_start..].strip_prefix(VARIABLE_PREFIX) {
+        Some(name) => name,
+        None => return Err((column(line, name_start), expected())),
+    };
+    let (name, value) = match name.split_once(VARIABLE_ASSIGNMENT) {
+        Some((name, value)) => (name.trim(), value),
+        None => return Err((column(line, line.trim_end().len()), expected())),
+    };
+    if !is_variable_name(name) {
+        return Err((
+            column(line, name_start),
+            format!("Invalid variable name '${}'", name),
+        ));
+    }
+    let value_start = line.len() - value.trim_start().len();
+    return Ok(Definition {
+        kind: DefinitionKind::VARIABLE,
+        file: PathBuf::new(),
+        section: String::new(),
+        identifier: name.to_string(),
+        value: value.trim().to_string(),
+        line: line_number,
+        identifier_column: column(line, name_start),
+        value_column: column(line, value_start),
+        conditional: false,
+    });
+}
+
+// The section name is kept as written, quotes included, for the parser to make sense of
+fn tokenize_section(
+    header: &str,
+    start_column: usize,
+    line_number: usize,
+) -> Result<Definition, (usize, String)> {
+    let name = match header.strip_suffix(SECTION_END) {
+        Some(name) => name.trim(),
+        None => {
+            return Err((
+                start_column + header.chars().count() + 1,
+                String::from("Expected ']' after the section name"),
+            ))
+        }
+    };
+    if name.is_empty() {
+        return Err((start_column, String::from("Missing section name")));
+    }
+    return Ok(Definition {
+        kind: DefinitionKind::SECTION,
+        file: PathBuf::new(),
+        section: String::new(),
+        identifier: name.to_string(),
+        value: String::new(),
+        line: line_number,
+        identifier_column: start_column,
+        value_column: start_column,
+        conditional: false,
+    });
+}
+
+fn tokenize_condition(
+    line: &str,
+    start: usize,
+    line_number: usize,
+) -> Result<Definition, (usize, String)> {
+    let (condition, value) = match line[start..].split_once(CONDITION_ASSIGNMENT) {
+        Some((condition, value)) => (condition.trim(), value),
+        None => {
+            return Err((
+                column(line, start),
+                String::from("Expected 'if <condition> = <value>'"),
+            ))
+        }
+    };
+    if condition.is_empty() {
+        return Err((column(line, start), String::from("Missing condition")));
+    }
+    let value_start = line.len() - value.trim_start().len();
+    return Ok(Definition {
+        kind: DefinitionKind::CONDITION,
+        file: PathBuf::new(),
+        section: String::new(),
+        identifier: condition.to_string(),
+        value: value.trim().to_string(),
+        line: line_number,
+        identifier_column: column(line, start),
+        value_column: column(line, value_start),
+        conditional: false,
+    });
+}
+
+// Definitions of includes and the end of conditions, which have no value of their own
+pub fn directive(kind: DefinitionKind, identifier: &str, line: usize, column: usize) -> Definition {
+    return Definition {
+        kind,
+        file: PathBuf::new(),
+        section: String::new(),
+        identifier: identifier.to_string(),
+        value: String::new(),
+        line,
+        identifier_column: column,
+        value_column: column,
+        conditional: false,
+    };
+}
+
+// Variable names are made of letters, digits and underscores
+pub fn is_variable_name(name: &str) -> bool {
+    return !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+// Strip a leading keyword followed by whitespace, ignoring case
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let prefix = text.get(..keyword.len())?;
+    let rest = &text[keyword.len()..];
+    if !prefix.eq_ignore_ascii_case(keyword) || !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    return Some(rest);
+}
+
+/*
+   A comment starts at a '#' beginning the line, or a '#' between whitespace elsewhere, so colors
+   like #FF0000 don't need quotes. Quoted text never contains a comment
+*/
+fn strip_comment(line: &str) -> Result<&str, (usize, String)> {
+    let mut quote_start: Option<usize> = None;
+    let mut previous: Option<char> = None;
+    for (index, char) in line.char_indices() {
+        if char == QUOTE {
+            quote_start = match quote_start {
+                Some(_) => None,
+                None => Some(index),
+            };
+        } else if char == COMMENT && quote_start.is_none() {
+            let next = line[index + 1..].chars().next();
+            let starts_line = line[..index].trim().is_empty();
+            let spaced =
+                previous.is_some_and(char::is_whitespace) && next.is_none_or(char::is_whitespace);
+            if starts_line || spaced {
+                return Ok(&line[..index]);
+            }
+        }
+        previous = Some(char);
+    }
+    return match quote_start {
+        Some(index) => Err((column(line, index), String::from("Unterminated quote"))),
+        None => Ok(line),
+    };
+}
+
+// Split text at every separator outside of quotes, keeping the column each part starts at
+pub fn split_unquoted(text: &str, separator: char, start_column: usize) -> Vec<Token> {
+    let mut parts: Vec<Token> = Vec::new();
+    let mut quoted = false;
+    let mut part_start = 0;
+    for (index, char) in text.char_indices() {
+        if char == QUOTE {
+            quoted = !quoted;
+        } else if char == separator && !quoted {
+            parts.push(trimmed_token(
+                &text[part_start..index],
+                start_column + column(text, part_start) - 1,
+            ));
+            part_start = index + char.len_utf8();
+        }
+    }
+    parts.push(trimmed_token(
+        &text[part_start..],
+        start_column + column(text, part_start) - 1,
+    ));
+    return parts;
+}
+
+pub fn trimmed_token(text: &str, start_column: usize) -> Token {
+    let leading = text.len() - text.trim_start().len();
+    return Token::new(text.trim(), start_column + text[..leading].chars().count());
+}
+
+// Remove the quotes around a value, if there are any
+pub fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    return match text
+        .strip_prefix(QUOTE)
+        .and_then(|text| text.strip_suffix(QUOTE))
+    {
+        Some(unquoted) => unquoted,
+        None => text,
+    };
+}
+
+// Columns count characters rather than bytes, starting from 1
+fn column(line: &str, byte_index: usize) -> usize {
+    return line[..byte_index].chars().count() + 1;
+}
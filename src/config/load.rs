@@ -1,15 +1,113 @@
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
-use log::error;
+use log::{error, info};
 
 use crate::config;
+use crate::config::lexer::ConfigError;
 
-pub fn ensure_exists(config_path: &Path) {
+const CONFIG_FILE: &str = "fenestra.conf";
+// Looked for in each directory, TOML first
+const CONFIG_FILES: &[&str] = &["fenestra.toml", CONFIG_FILE];
+const CONFIG_DIRECTORY: &str = "fenestra";
+const CONFIG_FLAG: &str = "--config";
+const CONFIG_VARIABLE: &str = "FENESTRA_CONFIG";
+
+/*
+   Find the config file, in order of precedence:
+   - the path given with --config <path>
+   - the path in the FENESTRA_CONFIG environment variable
+   - fenestra\fenestra.toml or fenestra\fenestra.conf in %APPDATA%, or in $XDG_CONFIG_HOME elsewhere
+   - fenestra.toml or fenestra.conf next to the executable
+   Paths given explicitly are used even when the file doesn't exist yet, so the default config is
+   written there. Otherwise the default config goes into the user config directory
+*/
+pub fn locate(args: &[String]) -> PathBuf {
+    let (config_path, source) = match find(args) {
+        Some(found) => found,
+        None => match user_config_directory().or_else(executable_directory) {
+            Some(directory) => (
+                directory.join(CONFIG_FILE),
+                "no config file found, using the default location",
+            ),
+            None => (PathBuf::from(CONFIG_FILE), "no config location found"),
+        },
+    };
+    info!("Using config file {} ({})", config_path.display(), source);
+    return config_path;
+}
+
+fn find(args: &[String]) -> Option<(PathBuf, &'static str)> {
+    if let Some(config_path) = flag_path(args) {
+        return Some((config_path, "given with --config"));
+    }
+    if let Some(config_path) = env::var_os(CONFIG_VARIABLE).filter(|value| !value.is_empty()) {
+        return Some((PathBuf::from(config_path), "set in FENESTRA_CONFIG"));
+    }
+    if let Some(config_path) = user_config_directory().and_then(existing_config) {
+        return Some((config_path, "found in the user config directory"));
+    }
+    if let Some(config_path) = executable_directory().and_then(existing_config) {
+        return Some((config_path, "found next to the executable"));
+    }
+    return None;
+}
+
+// Accepts both '--config <path>' and '--config=<path>'
+fn flag_path(args: &[String]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_FLAG {
+            return match args.next() {
+                Some(config_path) => Some(PathBuf::from(config_path)),
+                None => {
+                    error!("Expected a path after {}", CONFIG_FLAG);
+                    None
+                }
+            };
+        }
+        if let Some(config_path) = arg
+            .strip_prefix(CONFIG_FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(PathBuf::from(config_path));
+        }
+    }
+    return None;
+}
+
+fn existing_config(directory: PathBuf) -> Option<PathBuf> {
+    return CONFIG_FILES
+        .iter()
+        .map(|file| directory.join(file))
+        .find(|config_path| config_path.exists());
+}
+
+fn user_config_directory() -> Option<PathBuf> {
+    let config_home = match cfg!(windows) {
+        true => env::var_os("APPDATA").map(PathBuf::from),
+        false => env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config"))),
+    };
+    return config_home
+        .filter(|config_home| !config_home.as_os_str().is_empty())
+        .map(|config_home| config_home.join(CONFIG_DIRECTORY));
+}
+
+fn executable_directory() -> Option<PathBuf> {
+    let executable = env::current_exe().ok()?;
+    return executable.parent().map(Path::to_path_buf);
+}
+
+// Write the default config when there is none yet
+pub fn ensure_exists(config_path: &Path) -> Result<(), Vec<ConfigError>> {
     if !config_path.exists() {
         error!(
             "Unable to locate config file at {}",
             config_path.to_str().unwrap()
         );
-        config::write::default(config_path);
+        return config::write::default(config_path);
     }
+    return Ok(());
 }
@@ -0,0 +1,275 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use toml::{Table, Value};
+
+use crate::config::actions::macro_name;
+use crate::config::conflicts::warning;
+use crate::config::lexer::{tokenize, unquote, ConfigError, DefinitionKind};
+use crate::config::parse::{is_mode_name, parse_str};
+use crate::config::profile::Machine;
+use crate::data::config::{Section, SETTINGS};
+use crate::data::key::Keybind;
+
+const INCLUDE: &str = "include";
+const PROFILES: &str = "profiles";
+// Tables of the TOML config in the order they are written out
+const TABLES: &[&str] = &[
+    "general",
+    "layout",
+    "workspaces",
+    "monitors",
+    "rules",
+    "variables",
+    "macros",
+    "bindings",
+    "modes",
+];
+
+/*
+   Convert a config in the legacy format into the equivalent fenestra.toml. The legacy config is
+   parsed first, so only valid configs are converted, and the result is parsed again to make sure
+   every keybind comes out the same. Conditional blocks become [[profiles]] and includes are kept as
+   they are, so included files stay in the legacy format. Comments are not carried over
+*/
+pub fn to_toml(file: &Path, content: &str, machine: &Machine) -> Result<String, Vec<ConfigError>> {
+    let config = parse_str(file, content, machine)?;
+    let (definitions, _) = tokenize(file, content);
+    // The config itself, followed by the profiles of the conditions open at the definition
+    let mut stack: Vec<Table> = vec![empty_tables()];
+    for definition in definitions {
+        let value = unquote(&definition.value).to_string();
+        let tables = stack.last_mut().unwrap();
+        let section = match definition.section.as_str() {
+            "" => Section::TOP,
+            section => Section::from_str(section).unwrap_or(Section::TOP),
+        };
+        match definition.kind {
+            DefinitionKind::ENTRY => {}
+            DefinitionKind::VARIABLE => {
+                add(
+                    table(tables, &["variables"]),
+                    &definition.identifier,
+                    Value::String(value),
+                );
+                continue;
+            }
+            DefinitionKind::INCLUDE => {
+                add(
+                    tables,
+                    INCLUDE,
+                    Value::String(definition.identifier.clone()),
+                );
+                continue;
+            }
+            DefinitionKind::CONDITION => {
+                let mut profile = Table::new();
+                profile.insert(definition.identifier.clone(), setting(&value));
+                profile.extend(empty_tables());
+                stack.push(profile);
+                continue;
+            }
+            DefinitionKind::END => {
+                let profile = finish(stack.pop().unwrap());
+                let profiles = stack
+                    .last_mut()
+                    .unwrap()
+                    .entry(PROFILES)
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(profiles) = profiles {
+                    profiles.push(Value::Table(profile));
+                }
+                continue;
+            }
+            DefinitionKind::SECTION => continue,
+        }
+        let identifier = definition.identifier.as_str();
+        if let Section::MONITOR(name) = &section {
+            add(
+                table(tables, &["monitors", name]),
+                identifier,
+                setting(&value),
+            );
+            continue;
+        }
+        let setting_section = SETTINGS
+            .iter()
+            .find(|(name, _)| *name == identifier)
+            .map(|(_, section)| section);
+        if let Some(setting_section) = setting_section {
+            // Settings at the top of the legacy config move into the section they belong in
+            let name = match setting_section {
+                Section::LAYOUT => "layout",
+                Section::WORKSPACES => "workspaces",
+                Section::RULES => "rules",
+                _ => "general",
+            };
+            add(table(tables, &[name]), identifier, setting(&value));
+            continue;
+        }
+        if let Some(name) = macro_name(identifier) {
+            add(table(tables, &["macros"]), &name, Value::String(value));
+            continue;
+        }
+        // Keybinds keep their value as written, options and variables included
+        let value = Value::String(definition.value.clone());
+        match identifier.split_once('.') {
+            Some((mode, action)) if is_mode_name(mode) => {
+                add(table(tables, &["modes", mode.trim()]), action.trim(), value)
+            }
+            _ => add(table(tables, &["bindings"]), identifier, value),
+        }
+    }
+
+    let migrated = format!(
+        "# Fenestra config, migrated from {}\n\n{}",
+        file.display(),
+        toml::to_string(&finish(stack.remove(0))).unwrap_or_default()
+    );
+    let migrated_config = parse_str(&file.with_extension("toml"), &migrated, machine)?;
+    let errors = compare_keybinds(&config.keybinds, &migrated_config.keybinds);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    return Ok(migrated);
+}
+
+/*
+   Every keybind has to come out of the migration with the same keys, binding mode, actions and
+   options. The TOML config groups keybinds by table, so the order isn't compared
+*/
+fn compare_keybinds(keybinds: &[Keybind], migrated: &[Keybind]) -> Vec<ConfigError> {
+    let mut errors: Vec<ConfigError> = Vec::new();
+    let mut unmatched: Vec<&Keybind> = migrated.iter().collect();
+    for keybind in keybinds {
+        match unmatched
+            .iter()
+            .position(|migrated| signature(migrated) == signature(keybind))
+        {
+            Some(index) => {
+                unmatched.remove(index);
+            }
+            None => errors.push(warning(
+                keybind,
+                format!(
+                    "The migrated config loses or changes this keybind ({})",
+                    signature(keybind)
+                ),
+            )),
+        }
+    }
+    for keybind in unmatched {
+        errors.push(warning(
+            keybind,
+            format!(
+                "The migrated config adds a keybind ({})",
+                signature(keybind)
+            ),
+        ));
+    }
+    return errors;
+}
+
+fn signature(keybind: &Keybind) -> String {
+    let actions: Vec<String> = keybind
+        .actions
+        .iter()
+        .map(|action| action.to_string())
+        .collect();
+    return format!(
+        "{}{} = {} [{}]",
+        keybind
+            .mode
+            .as_ref()
+            .map(|mode| format!("{}.", mode))
+            .unwrap_or_default(),
+        actions.join("; "),
+        keybind.sequence_string(),
+        keybind.options()
+    );
+}
+
+// Empty tables in the order they are written out
+fn empty_tables() -> Table {
+    return TABLES
+        .iter()
+        .map(|name| (name.to_string(), Value::Table(Table::new())))
+        .collect();
+}
+
+// Drop the tables nothing was written to
+fn finish(mut tables: Table) -> Table {
+    tables.retain(|_, value| value.as_table().is_none_or(|table| !table.is_empty()));
+    return tables;
+}
+
+// The table at the path, created along the way when missing
+fn table<'a>(tables: &'a mut Table, path: &[&str]) -> &'a mut Table {
+    let mut table = tables;
+    for name in path {
+        table = table
+            .entry(name.to_string())
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .unwrap();
+    }
+    return table;
+}
+
+// Keys given more than once, like keybinds for the same action, are collected into an array
+fn add(table: &mut Table, key: &str, value: Value) {
+    match table.get_mut(key) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => *existing = Value::Array(vec![existing.clone(), value]),
+        None => {
+            table.insert(key.to_string(), value);
+        }
+    }
+}
+
+// Settings are written with the TOML type matching their value
+fn setting(value: &str) -> Value {
+    if let Ok(number) = i64::from_str(value) {
+        return Value::Integer(number);
+    }
+    return match value.to_ascii_lowercase().as_str() {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => Value::String(value.to_string()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::config::migrate::{compare_keybinds, to_toml};
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+
+    #[test]
+    fn migrates_the_basic_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config/basic.conf");
+        let content = fs::read_to_string(&path).unwrap();
+        let migrated = to_toml(Path::new("basic.conf"), &content, &Machine::default());
+        assert!(migrated.is_ok(), "{:?}", migrated.err());
+    }
+
+    #[test]
+    fn changed_keybinds_are_reported() {
+        let machine = Machine::default();
+        let parse = |content: &str| {
+            return parse_str(Path::new("fenestra.conf"), content, &machine)
+                .unwrap()
+                .keybinds;
+        };
+        let keybinds = parse("close_window: WIN + q\nfocus_window_left: WIN + h\n");
+        let same = parse("focus_window_left: WIN + h\nclose_window: WIN + q\n");
+        let changed = parse("close_window: WIN + q\nfocus_window_right: WIN + h\n");
+        assert!(compare_keybinds(&keybinds, &same).is_empty());
+        let errors = compare_keybinds(&keybinds, &changed);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, 2);
+    }
+}
@@ -1,3 +1,11 @@
+pub mod actions;
+pub mod bindings;
+pub mod conflicts;
+pub mod lexer;
 pub mod load;
+pub mod migrate;
 pub mod parse;
+pub mod profile;
+pub mod toml_config;
+pub mod variables;
 pub mod write;
@@ -1,125 +1,555 @@
-use std::{fs, io};
 use std::collections::HashMap;
+use std::fs;
 use std::path::Path;
-use std::process::exit;
 use std::str::FromStr;
 
-use log::error;
-
+use crate::config::actions::{macro_name, parse_actions};
+use crate::config::conflicts;
+use crate::config::lexer::{
+    split_unquoted, tokenize, trimmed_token, unquote, ConfigError, Definition, DefinitionKind,
+    Token, QUOTE,
+};
+use crate::config::profile::{self, Machine};
+use crate::config::toml_config;
+use crate::config::variables::{Variable, Variables};
 use crate::data::action::WindowManagerAction;
-use crate::data::key::{Key, Keybind};
+use crate::data::config::{Config, Section, Settings};
+use crate::data::key::{is_mouse_key, Key, Keybind, KEY_ESCAPE};
+use crate::data::sequence::Trigger;
+use crate::data::window::WindowRule;
+
+// Chords of a key sequence are separated by ',', the keys of a chord by '+'
+const SEQUENCE_SEPARATOR: char = ',';
+const CHORD_SEPARATOR: char = '+';
+const OPTIONS_START: char = '[';
+const OPTIONS_END: char = ']';
+const OPTION_SEPARATOR: char = ',';
+// Bound to ESCAPE in binding modes without a way out
+const EXIT_MODE: &str = "exit_mode";
+
+pub fn parse_content(config_path: &Path, machine: &Machine) -> Result<Config, Vec<ConfigError>> {
+    let config_content = fs::read_to_string(config_path).map_err(|error| {
+        vec![ConfigError::new(
+            config_path,
+            0,
+            0,
+            format!("Failed to read config file: {}", error),
+        )]
+    })?;
+    return parse_str(config_path, &config_content, machine);
+}
+
+/*
+   Parse config content, reporting every error found along with where it was found. Included files
+   are read and conditional blocks are applied for the given machine
+*/
+pub fn parse_str(
+    config_path: &Path,
+    config_content: &str,
+    machine: &Machine,
+) -> Result<Config, Vec<ConfigError>> {
+    let (definitions, mut errors) = tokenize_file(config_path, config_content);
+    let (definitions, profile_errors) = profile::resolve(config_path, definitions, machine);
+    errors.extend(profile_errors);
+    return parse_definitions(definitions, errors).map_err(|mut errors| {
+        errors.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        return errors;
+    });
+}
+
+pub fn tokenize_file(
+    config_path: &Path,
+    config_content: &str,
+) -> (Vec<Definition>, Vec<ConfigError>) {
+    return match is_toml(config_path) {
+        true => toml_config::tokenize(config_path, config_content),
+        false => tokenize(config_path, config_content),
+    };
+}
+
+// Configs are written in TOML when their file name says so, in the legacy format otherwise
+pub fn is_toml(config_path: &Path) -> bool {
+    return config_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+}
+
+fn parse_definitions(
+    definitions: Vec<Definition>,
+    mut errors: Vec<ConfigError>,
+) -> Result<Config, Vec<ConfigError>> {
+    let error = |definition: &Definition, column: usize, message: String| {
+        ConfigError::new(&definition.file, definition.line, column, message)
+    };
 
-pub fn parse_content(config_path: &Path) -> Vec<Keybind> {
-    let config_content_result: io::Result<String> = fs::read_to_string(config_path);
-    if config_content_result.is_err() {
-        error!("Failed to read config file");
-        exit(1);
+    // Sections by their name as written, None when the header is invalid and already reported
+    let mut sections: HashMap<&str, Option<Section>> = HashMap::new();
+    sections.insert("", Some(Section::TOP));
+    for header in definitions
+        .iter()
+        .filter(|definition| definition.kind == DefinitionKind::SECTION)
+    {
+        let section = match Section::from_str(&header.identifier) {
+            Ok(section) => Some(section),
+            Err(message) => {
+                errors.push(error(header, header.identifier_column, message));
+                None
+            }
+        };
+        sections.entry(&header.identifier).or_insert(section);
+    }
+    for definition in definitions.iter().filter(|definition| {
+        matches!(
+            definition.kind,
+            DefinitionKind::ENTRY | DefinitionKind::VARIABLE
+        ) && !sections.contains_key(definition.section.as_str())
+    }) {
+        errors.push(error(
+            definition,
+            definition.identifier_column,
+            format!(
+                "Definition outside of a known section '{}'",
+                definition.section
+            ),
+        ));
     }
+    let in_section = |kind: DefinitionKind| {
+        definitions
+            .iter()
+            .filter(move |definition| definition.kind == kind)
+            .filter_map(|definition| {
+                sections
+                    .get(definition.section.as_str())
+                    .cloned()
+                    .flatten()
+                    .map(|section| (section, definition))
+            })
+    };
+    let entries: Vec<(Section, &Definition)> = in_section(DefinitionKind::ENTRY).collect();
+    let is_setting = |(section, definition): &&(Section, &Definition)| {
+        Settings::is_setting(section, &definition.identifier)
+    };
 
-    let config_content: String = config_content_result.unwrap();
-    let config_lines: Vec<String> = config_content
-        .split("\r\n")
-        .filter(|line| line.len() > 0)
-        .map(|line| String::from(line))
-        .collect();
-    let mut key_combos: Vec<Keybind> = Vec::new();
-    let mut config_definitions: Vec<(String, Vec<String>)> = config_lines
+    // Settings are plain values rather than key combos, handle them separately
+    let mut settings = Settings::default();
+    for (section, definition) in entries.iter().filter(is_setting) {
+        if let Err(message) =
+            settings.apply(section, &definition.identifier, unquote(&definition.value))
+        {
+            errors.push(error(definition, definition.value_column, message));
+        }
+    }
+    settings.finish();
+    let entries: Vec<&Definition> = entries
         .iter()
-        .map(|config_line| {
-            let definition: Vec<&str> = config_line.split(":").collect();
-            let config_action = definition[0].trim().to_string();
-            let key_combo = definition[1]
-                .split("+")
-                .map(|val| val.trim().to_string())
-                .collect();
-            return (config_action, key_combo);
-        })
+        .filter(|entry| !is_setting(entry))
+        .map(|(_, definition)| *definition)
         .collect();
 
-    let config_variables: HashMap<String, String> = config_definitions
+    // Macros hold a list of actions rather than key combos as well
+    let macros: HashMap<String, String> = entries
         .iter()
-        .filter(|definition| is_variable(&definition.0, &config_content))
-        .map(|definition| {
-            (
-                definition.0.to_owned(),
-                definition
-                    .1
-                    .iter()
-                    .map(|key| key.to_string())
-                    .collect::<Vec<_>>()
-                    .join("+"),
-            )
+        .filter_map(|definition| {
+            macro_name(&definition.identifier).map(|name| (name, definition.value.clone()))
         })
         .collect();
 
-    config_definitions = config_definitions
+    let mut declarations: Vec<Variable> = Vec::new();
+    for (section, definition) in in_section(DefinitionKind::VARIABLE) {
+        if !matches!(section, Section::TOP | Section::BINDINGS) {
+            errors.push(error(
+                definition,
+                definition.identifier_column,
+                String::from("Variables are declared in the [bindings] section"),
+            ));
+            continue;
+        }
+        declarations.push(Variable {
+            name: definition.identifier.clone(),
+            file: definition.file.clone(),
+            tokens: key_tokens(&definition.value, definition.value_column),
+            line: definition.line,
+            column: definition.identifier_column,
+            conditional: definition.conditional,
+        });
+    }
+    let (mut variables, variable_errors) = Variables::new(declarations);
+    errors.extend(variable_errors);
+
+    let mut key_combos: Vec<Keybind> = Vec::new();
+    for definition in entries
         .iter()
-        .map(|definition| {
-            let config_action = definition.0.to_owned();
-            let key_combo = definition.1.to_owned();
-            let mut expanded_key_combo: Vec<String> = Vec::new();
-            key_combo.iter().for_each(|key| {
-                if !key.starts_with("$") {
-                    expanded_key_combo.push(key.to_string());
-                    return;
-                }
-
-                let var_name = String::from(key.strip_prefix("$").unwrap());
-                if config_variables.contains_key(&var_name) {
-                    let result = config_variables.get(&var_name);
-                    if result.is_none() {
-                        error!("Config referenced non-existent variable {}", var_name);
-                        exit(100);
-                    }
-                    let mut expanded_var_keys: Vec<String> = result
-                        .unwrap()
-                        .split("+")
-                        .map(|k| k.trim().to_string())
-                        .collect();
-                    expanded_key_combo.append(&mut expanded_var_keys);
-                }
-            });
-            return (config_action, expanded_key_combo);
-        })
+        .filter(|definition| macro_name(&definition.identifier).is_none())
+    {
+        match parse_keybind(definition, &mut variables, &macros) {
+            Ok(keybind) => key_combos.push(keybind),
+            Err((column, message)) => errors.push(error(definition, column, message)),
+        }
+    }
+    errors.extend(variables.unused().iter().map(|variable| {
+        ConfigError::new(
+            &variable.file,
+            variable.line,
+            variable.column,
+            format!("Variable ${} is never used", variable.name),
+        )
+    }));
+    // Variables used to be declared like keybinds, point those configs at the new syntax
+    for config_error in errors.iter_mut() {
+        let name = match config_error.message.strip_prefix("Undefined variable $") {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if entries
+            .iter()
+            .any(|definition| definition.identifier == name)
+        {
+            config_error.message += &format!(
+                ", declare it as 'set ${} = ...' instead of '{}: ...'",
+                name, name
+            );
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut warnings = ensure_mode_exits(&mut key_combos);
+    let (conflict_errors, conflict_warnings) = conflicts::check_conflicts(&mut key_combos);
+    if !conflict_errors.is_empty() {
+        return Err(conflict_errors);
+    }
+    warnings.extend(conflict_warnings);
+    settings.mouse_bindings = key_combos.iter().any(|keybind| {
+        keybind
+            .sequence
+            .iter()
+            .flatten()
+            .any(|key| is_mouse_key(key.code))
+    });
+    // debug!("Parsed config: {:?}", key_combos);
+    return Ok(Config {
+        keybinds: key_combos,
+        settings,
+        warnings,
+    });
+}
+
+fn parse_keybind(
+    definition: &Definition,
+    variables: &mut Variables,
+    macros: &HashMap<String, String>,
+) -> Result<Keybind, (usize, String)> {
+    let config_identifier = &definition.identifier;
+    // Keybinds prefixed with '<mode>.' only apply while that binding mode is active
+    let (mode, config_action) = match config_identifier.split_once('.') {
+        Some((mode, action)) if is_mode_name(mode) => (
+            Some(mode.trim().to_ascii_lowercase()),
+            action.trim().to_string(),
+        ),
+        _ => (None, config_identifier.clone()),
+    };
+    let actions = parse_actions(&config_action, macros)
+        .map_err(|message| (definition.identifier_column, message))?;
+
+    // Trailing '[option, ...]' options follow the keys
+    let (keys, options) = split_options(&definition.value, definition.value_column)?;
+    let mut trigger = Trigger::default();
+    let mut rule = WindowRule::default();
+    for option in options {
+        // Options of the form 'name=value' limit the keybind to an application
+        let (name, value) = match option.text.split_once('=') {
+            Some((name, value)) => (
+                name.trim().to_ascii_lowercase(),
+                Some(unquote(value).to_string()),
+            ),
+            None => (option.text.to_ascii_lowercase(), None),
+        };
+        match (name.as_str(), value) {
+            ("exe", Some(value)) => rule.executable = Some(value),
+            ("class", Some(value)) => rule.class_name = Some(value),
+            ("title", Some(value)) => rule.title = Some(value),
+            ("on_release", None) => trigger.on_release = true,
+            ("repeat", None) => trigger.repeat = true,
+            _ => {
+                return Err((
+                    option.column,
+                    format!("Unknown keybind option '{}'", option.text),
+                ))
+            }
+        }
+    }
+
+    let key_combo = variables.expand(key_tokens(keys.text.as_str(), keys.column))?;
+    let mut sequence: Vec<Vec<Key>> = Vec::new();
+    for chord in key_combo.split(|key| key.text == SEQUENCE_SEPARATOR.to_string()) {
+        let mut keys: Vec<Key> = Vec::new();
+        for key in chord {
+            if key.text.is_empty() {
+                return Err((key.column, String::from("Missing key")));
+            }
+            keys.push(Key::from_str(unquote(&key.text)).map_err(|message| (key.column, message))?);
+        }
+        keys.sort();
+        sequence.push(keys);
+    }
+    let mut keybind = Keybind::new(sequence, actions, mode);
+    keybind.trigger = trigger;
+    if rule != WindowRule::default() {
+        keybind.rule = Some(rule);
+    }
+    keybind.file = definition.file.clone();
+    keybind.line = definition.line;
+    keybind.conditional = definition.conditional;
+    return Ok(keybind);
+}
+
+// Split a value into its keys and the options listed in square brackets after them
+fn split_options(value: &str, value_column: usize) -> Result<(Token, Vec<Token>), (usize, String)> {
+    let options_start = match find_options_start(value) {
+        Some(options_start) => options_start,
+        None => return Ok((trimmed_token(value, value_column), Vec::new())),
+    };
+    let keys = trimmed_token(&value[..options_start], value_column);
+    let options = trimmed_token(
+        &value[options_start + 1..],
+        value_column + value[..options_start + 1].chars().count(),
+    );
+    let nested = split_unquoted(&options.text, OPTIONS_START, options.column);
+    if nested.len() > 1 {
+        return Err((nested[1].column - 1, String::from("Unexpected '['")));
+    }
+    let options_text = match options.text.strip_suffix(OPTIONS_END) {
+        Some(options_text) => options_text,
+        None => {
+            return Err((
+                options.column + options.text.chars().count(),
+                String::from("Expected ']' after the keybind options"),
+            ))
+        }
+    };
+    let options = split_unquoted(options_text, OPTION_SEPARATOR, options.column)
+        .into_iter()
+        .filter(|option| !option.text.is_empty())
         .collect();
+    return Ok((keys, options));
+}
+
+/*
+   The options start at the first unquoted '[' following a key. A '[' at the start of the value or
+   right after a separator is the key itself, so 'WIN + [' and 'WIN + [ [repeat]' need no quotes
+*/
+fn find_options_start(value: &str) -> Option<usize> {
+    let mut quoted = false;
+    let mut previous: Option<char> = None;
+    for (index, char) in value.char_indices() {
+        if char == QUOTE {
+            quoted = !quoted;
+        } else if char == OPTIONS_START && !quoted {
+            match previous {
+                None | Some(CHORD_SEPARATOR) | Some(SEQUENCE_SEPARATOR) => {}
+                Some(_) => return Some(index),
+            }
+        }
+        if !char.is_whitespace() {
+            previous = Some(char);
+        }
+    }
+    return None;
+}
 
-    let config_action_mappings: Vec<(String, Vec<String>)> = config_definitions
+// Keys of each chord, with a SEQUENCE_SEPARATOR token between the chords
+fn key_tokens(keys: &str, column: usize) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    for chord in split_unquoted(keys, SEQUENCE_SEPARATOR, column) {
+        if !tokens.is_empty() {
+            tokens.push(Token::new(&SEQUENCE_SEPARATOR.to_string(), chord.column));
+        }
+        tokens.extend(split_unquoted(&chord.text, CHORD_SEPARATOR, chord.column));
+    }
+    return tokens;
+}
+
+// A mode without an exit_mode keybind would capture the keyboard for good, fall back to ESCAPE
+fn ensure_mode_exits(key_combos: &mut Vec<Keybind>) -> Vec<ConfigError> {
+    let mut warnings: Vec<ConfigError> = Vec::new();
+    let mut modes: Vec<String> = key_combos
         .iter()
-        .filter(|definition| !&config_variables.contains_key(&definition.0))
-        .map(|definition| definition.to_owned())
+        .filter_map(|keybind| keybind.mode.clone())
         .collect();
+    modes.sort();
+    modes.dedup();
+    for mode in modes {
+        let has_exit = key_combos.iter().any(|keybind| {
+            keybind.mode.as_ref() == Some(&mode)
+                && keybind
+                    .actions
+                    .iter()
+                    .any(|action| matches!(action, WindowManagerAction::ExitMode(_)))
+        });
+        if has_exit {
+            continue;
+        }
+        // Pointing at the first keybind of the mode
+        let first = key_combos
+            .iter()
+            .find(|keybind| keybind.mode.as_ref() == Some(&mode))
+            .unwrap();
+        warnings.push(conflicts::warning(
+            first,
+            format!(
+                "Binding mode {} has no exit_mode keybind, binding ESCAPE",
+                mode
+            ),
+        ));
+        let exit = Keybind::new(
+            vec![vec![Key::from(KEY_ESCAPE)]],
+            vec![WindowManagerAction::from_str(EXIT_MODE).unwrap()],
+            Some(mode),
+        );
+        key_combos.push(exit);
+    }
+    for keybind in key_combos.iter() {
+        for action in keybind.actions.iter() {
+            let mode = match action {
+                WindowManagerAction::EnterMode(action) => &action.name,
+                _ => continue,
+            };
+            if !key_combos
+                .iter()
+                .any(|keybind| keybind.mode.as_ref() == Some(mode))
+            {
+                warnings.push(conflicts::warning(
+                    keybind,
+                    format!("Binding mode {} is entered but has no keybinds", mode),
+                ));
+            }
+        }
+    }
+    return warnings;
+}
 
-    config_action_mappings
-        .iter()
-        .for_each(|config_action_mapping| {
-            let config_action = config_action_mapping.0.to_owned();
-            let key_combo = config_action_mapping.1.to_owned();
-            let action = WindowManagerAction::from_str(&config_action.as_str());
-            if action.is_err() {
-                let current_line = config_lines
+pub fn is_mode_name(mode: &str) -> bool {
+    let mode = mode.trim();
+    return !mode.is_empty() && mode.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::config::bindings::{render, BindingsFormat};
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+
+    // Set to write the output of the fixtures to their .expected file instead of comparing it
+    const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+    fn fixture(name: &str) -> PathBuf {
+        return Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/config")
+            .join(name);
+    }
+
+    // The errors of the config, or its warnings followed by the keybind table
+    fn parse_output(name: &str, content: &str) -> String {
+        return match parse_str(Path::new(name), content, &Machine::default()) {
+            Ok(config) => {
+                let warnings: String = config
+                    .warnings
                     .iter()
-                    .position(|line| line.starts_with(&config_action.as_str()))
-                    .unwrap();
-                error!(
-                    "Invalid action name {} in config line {}",
-                    config_action, current_line
-                );
-                return;
+                    .map(|warning| format!("warning: {}\n", warning))
+                    .collect();
+                warnings + &render(&config.keybinds, BindingsFormat::TEXT)
             }
-            let action= WindowManagerAction::from_str(config_action.as_str()).unwrap();
-            let mut keys: Vec<Key> = key_combo
+            Err(errors) => errors
                 .iter()
-                .map(|key| Key::from_str(key.as_str()).unwrap())
-                .collect();
-            keys.sort();
-            key_combos.push(Keybind::new(keys, action));
-        });
-    // debug!("Parsed config: {:?}", key_combos);
-    return key_combos;
-}
+                .map(|error| format!("error: {}\n", error))
+                .collect(),
+        };
+    }
+
+    fn assert_golden(name: &str) {
+        let content = fs::read_to_string(fixture(name)).unwrap();
+        let output = parse_output(name, &content);
+        let expected_path = fixture(name).with_extension("expected");
+        if std::env::var_os(UPDATE_GOLDEN).is_some() {
+            fs::write(&expected_path, &output).unwrap();
+        }
+        let expected = fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(output, expected, "{} differs from its .expected file", name);
+    }
+
+    #[test]
+    fn parses_comments_quotes_and_sections() {
+        assert_golden("basic.conf");
+    }
 
-fn is_variable(key: &str, config_content: &String) -> bool {
-    let potential_var_name: String = String::from("$") + key;
-    let is_variable: bool = config_content.contains(&potential_var_name.as_str());
-    return is_variable;
+    #[test]
+    fn reports_errors_with_file_line_and_column() {
+        assert_golden("errors.conf");
+    }
+
+    #[test]
+    fn crlf_and_lf_parse_the_same() {
+        let content = fs::read_to_string(fixture("basic.conf")).unwrap();
+        let crlf = content.replace('\n', "\r\n");
+        assert_eq!(
+            parse_output("basic.conf", &crlf),
+            parse_output("basic.conf", &content)
+        );
+    }
+
+    #[test]
+    fn brackets_are_keys_unless_they_follow_a_key() {
+        let content = "focus_window_left: WIN + ALT + [\nfocus_window_right: WIN + ALT + ] [repeat]\nfocus_window_up: WIN + [ [on_release]\n";
+        let config = parse_str(Path::new("fenestra.conf"), content, &Machine::default()).unwrap();
+        let has_key = |index: usize, code: i32| {
+            config.keybinds[index].sequence[0]
+                .iter()
+                .any(|key| key.code == code)
+        };
+        // VK_OEM_4 and VK_OEM_6
+        assert!(has_key(0, 0xDB));
+        assert!(has_key(1, 0xDD));
+        assert!(has_key(2, 0xDB));
+        assert!(!config.keybinds[0].trigger.repeat);
+        assert!(config.keybinds[1].trigger.repeat);
+        assert!(config.keybinds[2].trigger.on_release);
+    }
+
+    #[test]
+    fn unclosed_and_repeated_options_are_errors() {
+        assert_eq!(
+            parse_output("fenestra.conf", "close_window: WIN + q [repeat\n"),
+            "error: fenestra.conf:1:30: Expected ']' after the keybind options\n"
+        );
+        assert_eq!(
+            parse_output(
+                "fenestra.conf",
+                "close_window: WIN + q [repeat] [on_release]\n"
+            ),
+            "error: fenestra.conf:1:32: Unexpected '['\n"
+        );
+    }
+
+    #[test]
+    fn exec_command_lines_need_no_quotes() {
+        let content = "exec: cmd /c echo a:b: WIN + e [exe=C:\\apps\\app.exe]\nexec(workspace=0, cwd=C:\\dev): code.exe .: WIN + c\n\"exec: wt.exe\": WIN + ENTER\n";
+        let config = parse_str(Path::new("fenestra.conf"), content, &Machine::default()).unwrap();
+        let actions: Vec<String> = config
+            .keybinds
+            .iter()
+            .map(|keybind| keybind.actions[0].to_string())
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                "exec: cmd /c echo a:b",
+                "exec(workspace=0, cwd=C:\\dev): code.exe .",
+                "exec: wt.exe",
+            ]
+        );
+        assert_eq!(config.keybinds[0].options(), "exe=C:\\apps\\app.exe");
+    }
 }
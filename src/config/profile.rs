@@ -0,0 +1,356 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config::lexer::{ConfigError, Definition, DefinitionKind};
+use crate::config::parse::tokenize_file;
+
+const HOSTNAME: &str = "hostname";
+const MONITOR_COUNT: &str = "monitor_count";
+const MONITOR: &str = "monitor";
+
+// What conditional blocks of the config are checked against
+#[derive(Clone, Debug, Default)]
+pub struct Machine {
+    pub hostname: String,
+    // Device names of the connected monitors, ex. 'DISPLAY1'
+    pub monitors: Vec<String>,
+}
+
+impl Machine {
+    pub fn new(monitors: Vec<String>) -> Self {
+        Machine {
+            hostname: hostname(),
+            monitors,
+        }
+    }
+
+    fn matches(&self, condition: &str, value: &str) -> Result<bool, String> {
+        return match condition.to_ascii_lowercase().as_str() {
+            HOSTNAME => Ok(self.hostname.eq_ignore_ascii_case(value)),
+            MONITOR_COUNT => match usize::from_str(value) {
+                Ok(count) => Ok(self.monitors.len() == count),
+                Err(_) => Err(format!("Invalid monitor count '{}'", value)),
+            },
+            // Monitors may be named with or without the '\\.\' device prefix, like monitor sections
+            MONITOR => Ok(self.monitors.iter().any(|monitor| {
+                monitor
+                    .trim_start_matches(['\\', '.'])
+                    .eq_ignore_ascii_case(value.trim_start_matches(['\\', '.']))
+            })),
+            _ => Err(format!(
+                "Unknown condition {}, expected {}, {} or {}",
+                condition, HOSTNAME, MONITOR_COUNT, MONITOR
+            )),
+        };
+    }
+}
+
+// COMPUTERNAME is set on Windows, the others let configs be checked elsewhere
+fn hostname() -> String {
+    return ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|variable| env::var(variable).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .unwrap_or_default();
+}
+
+/*
+   Read included files in place of their include and drop the definitions of blocks whose
+   condition doesn't hold on this machine. Includes are relative to the file including them, and
+   only read when their block applies, so a file which only exists on some machines can be
+   included from a block for those machines
+*/
+pub fn resolve(
+    config_path: &Path,
+    definitions: Vec<Definition>,
+    machine: &Machine,
+) -> (Vec<Definition>, Vec<ConfigError>) {
+    let mut including = vec![canonical(config_path)];
+    return resolve_file(definitions, machine, &mut including, &mut Vec::new());
+}
+
+/*
+   The config file and every file it includes on this machine, whether or not they could be read.
+   Watching them all picks up changes to included files, and included files being created
+*/
+pub fn config_files(config_path: &Path, machine: &Machine) -> Vec<PathBuf> {
+    let mut files = vec![config_path.to_path_buf()];
+    if let Ok(content) = fs::read_to_string(config_path) {
+        let (definitions, _) = tokenize_file(config_path, &content);
+        let mut including = vec![canonical(config_path)];
+        resolve_file(definitions, machine, &mut including, &mut files);
+    }
+    return files;
+}
+
+fn resolve_file(
+    definitions: Vec<Definition>,
+    machine: &Machine,
+    including: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> (Vec<Definition>, Vec<ConfigError>) {
+    let mut resolved: Vec<Definition> = Vec::new();
+    let mut errors: Vec<ConfigError> = Vec::new();
+    // Open conditions and whether they hold
+    let mut conditions: Vec<(Definition, bool)> = Vec::new();
+    for definition in definitions {
+        let active = conditions.iter().all(|(_, holds)| *holds);
+        let error = |message: String| {
+            ConfigError::new(
+                &definition.file,
+                definition.line,
+                definition.identifier_column,
+                message,
+            )
+        };
+        match definition.kind {
+            DefinitionKind::CONDITION => {
+                let holds = match machine.matches(&definition.identifier, &definition.value) {
+                    Ok(holds) => holds,
+                    Err(message) => {
+                        errors.push(error(message));
+                        false
+                    }
+                };
+                conditions.push((definition, holds));
+            }
+            DefinitionKind::END => {
+                if conditions.pop().is_none() {
+                    errors.push(error(String::from("'end' without a matching 'if'")));
+                }
+            }
+            // Sections are kept regardless, the definitions following them still refer to them
+            DefinitionKind::SECTION => resolved.push(definition),
+            _ if !active => {}
+            DefinitionKind::INCLUDE => {
+                let (included, include_errors) = include(&definition, machine, including, files);
+                resolved.extend(included);
+                errors.extend(include_errors);
+            }
+            _ => {
+                let mut definition = definition;
+                definition.conditional = !conditions.is_empty();
+                resolved.push(definition);
+            }
+        }
+    }
+    for (condition, _) in conditions {
+        errors.push(ConfigError::new(
+            &condition.file,
+            condition.line,
+            condition.identifier_column,
+            "'if' without a matching 'end'",
+        ));
+    }
+    return (resolved, errors);
+}
+
+fn include(
+    definition: &Definition,
+    machine: &Machine,
+    including: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> (Vec<Definition>, Vec<ConfigError>) {
+    let error = |message: String| {
+        vec![ConfigError::new(
+            &definition.file,
+            definition.line,
+            definition.identifier_column,
+            message,
+        )]
+    };
+    if definition.identifier.is_empty() {
+        return (Vec::new(), error(String::from("Missing path to include")));
+    }
+    let path = match definition.file.parent() {
+        Some(directory) => directory.join(&definition.identifier),
+        None => PathBuf::from(&definition.identifier),
+    };
+    if !files.contains(&path) {
+        files.push(path.clone());
+    }
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(read_error) => {
+            return (
+                Vec::new(),
+                error(format!(
+                    "Failed to read included file {}: {}",
+                    path.display(),
+                    read_error
+                )),
+            )
+        }
+    };
+    let canonical_path = canonical(&path);
+    if let Some(start) = including.iter().position(|file| *file == canonical_path) {
+        let cycle: Vec<String> = including[start..]
+            .iter()
+            .chain([&canonical_path])
+            .map(|file| file.display().to_string())
+            .collect();
+        return (
+            Vec::new(),
+            error(format!(
+                "Files include each other in a cycle: {}",
+                cycle.join(" -> ")
+            )),
+        );
+    }
+    let (definitions, mut errors) = tokenize_file(&path, &content);
+    including.push(canonical_path);
+    let (definitions, include_errors) = resolve_file(definitions, machine, including, files);
+    including.pop();
+    errors.extend(include_errors);
+    return (definitions, errors);
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    return fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::config::parse::tokenize_file;
+    use crate::config::profile::{config_files, resolve, Machine};
+
+    fn fixture(name: &str) -> PathBuf {
+        return Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/config/include")
+            .join(name);
+    }
+
+    fn machine() -> Machine {
+        return Machine {
+            hostname: String::from("desk"),
+            monitors: vec![
+                String::from("\\\\.\\DISPLAY1"),
+                String::from("\\\\.\\DISPLAY2"),
+            ],
+        };
+    }
+
+    // The identifiers which apply on the machine and the errors by line
+    fn resolve_file(path: &Path, content: &str) -> (Vec<String>, Vec<(usize, String)>) {
+        let (definitions, errors) = tokenize_file(path, content);
+        assert!(errors.is_empty());
+        let (definitions, errors) = resolve(path, definitions, &machine());
+        return (
+            definitions
+                .into_iter()
+                .map(|definition| definition.identifier)
+                .collect(),
+            errors
+                .into_iter()
+                .map(|error| (error.line, error.message))
+                .collect(),
+        );
+    }
+
+    fn resolve_fixture(name: &str) -> (Vec<String>, Vec<(usize, String)>) {
+        let path = fixture(name);
+        return resolve_file(&path, &fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn blocks_apply_by_hostname_monitor_count_and_monitor() {
+        let content = "\
+if hostname = DESK
+a: x
+end
+if hostname = laptop
+b: x
+end
+if monitor_count = 2
+c: x
+end
+if monitor_count = 1
+d: x
+end
+if monitor = DISPLAY2
+if monitor = \\\\.\\display1
+e: x
+end
+end
+if monitor = DISPLAY3
+f: x
+end
+";
+        let (identifiers, errors) = resolve_file(Path::new("fenestra.conf"), content);
+        assert_eq!(identifiers, vec!["a", "c", "e"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn invalid_conditions_are_errors() {
+        let content = "if monitor_count = two\na: x\nend\nif color = red\nend\n";
+        let (identifiers, errors) = resolve_file(Path::new("fenestra.conf"), content);
+        assert!(identifiers.is_empty());
+        assert_eq!(
+            errors,
+            vec![
+                (1, String::from("Invalid monitor count 'two'")),
+                (
+                    4,
+                    String::from(
+                        "Unknown condition color, expected hostname, monitor_count or monitor"
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unbalanced_blocks_are_errors() {
+        let content = "end\nif hostname = desk\na: x\n";
+        let (identifiers, errors) = resolve_file(Path::new("fenestra.conf"), content);
+        assert_eq!(identifiers, vec!["a"]);
+        assert_eq!(
+            errors,
+            vec![
+                (1, String::from("'end' without a matching 'if'")),
+                (2, String::from("'if' without a matching 'end'")),
+            ]
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let (identifiers, errors) = resolve_fixture("cycle_a.conf");
+        assert_eq!(identifiers, vec!["close_window"]);
+        assert_eq!(errors.len(), 1);
+        let (line, message) = &errors[0];
+        assert_eq!(*line, 2);
+        assert!(message.starts_with("Files include each other in a cycle: "));
+        assert!(message.ends_with("cycle_a.conf"));
+        assert!(message.contains("cycle_b.conf -> "));
+    }
+
+    #[test]
+    fn missing_includes_are_errors_unless_their_block_doesnt_apply() {
+        let (identifiers, errors) = resolve_fixture("missing.conf");
+        assert!(identifiers.is_empty());
+        assert_eq!(errors.len(), 1);
+        let (line, message) = &errors[0];
+        assert_eq!(*line, 4);
+        assert!(message.starts_with("Failed to read included file "));
+        assert!(message.contains("does_not_exist.conf"));
+    }
+
+    #[test]
+    fn config_files_list_the_included_files_of_this_machine() {
+        let files: Vec<String> = config_files(&fixture("missing.conf"), &machine())
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(files, vec!["missing.conf", "does_not_exist.conf"]);
+        let files = config_files(&fixture("cycle_a.conf"), &machine());
+        assert_eq!(files.len(), 2);
+    }
+}
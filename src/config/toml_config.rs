@@ -0,0 +1,642 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::Path;
+
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use crate::config::lexer::{directive, ConfigError, Definition, DefinitionKind};
+
+// Tables of fenestra.toml which don't map onto a section of the same name
+const VARIABLES_TABLE: &str = "variables";
+const MACROS_TABLE: &str = "macros";
+const MODES_TABLE: &str = "modes";
+const MONITORS_TABLE: &str = "monitors";
+const PROFILES_TABLE: &str = "profiles";
+const INCLUDE_KEY: &str = "include";
+const BINDINGS_SECTION: &str = "bindings";
+
+// Where a key or value starts in the file, lines and columns count from 1
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub position: Position,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TomlValue {
+    STRING(String),
+    INTEGER(i64),
+    BOOLEAN(bool),
+    // An inline table of strings, ex. '{ exe = "firefox.exe" }' for a window rule
+    RULE(Vec<(String, String)>),
+}
+
+// Values reach the settings and keybinds as the text they would have in the legacy format
+impl Display for TomlValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlValue::STRING(value) => write!(f, "{}", value),
+            TomlValue::INTEGER(value) => write!(f, "{}", value),
+            TomlValue::BOOLEAN(value) => write!(f, "{}", value),
+            TomlValue::RULE(parts) => {
+                let parts: Vec<String> = parts
+                    .iter()
+                    .map(|(key, value)| format!("{}=\"{}\"", key, value))
+                    .collect();
+                write!(f, "{}", parts.join(", "))
+            }
+        }
+    }
+}
+
+// A key with its values, an array holds one value per element
+#[derive(Clone, Debug)]
+pub struct TomlEntry {
+    pub key: Spanned<String>,
+    pub values: Vec<Spanned<TomlValue>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TomlTable {
+    pub name: Spanned<String>,
+    pub entries: Vec<TomlEntry>,
+}
+
+// The tables of a profile apply when all of its conditions hold
+#[derive(Clone, Debug)]
+pub struct TomlProfile {
+    pub position: Position,
+    pub conditions: Vec<TomlEntry>,
+    pub config: TomlConfig,
+}
+
+/*
+   fenestra.toml as it is written. The tables map onto the sections of the legacy format, with a few
+   additions the legacy format writes inline:
+   - [variables] holds the 'set $name = keys' declarations, without the '$'
+   - [macros] holds the 'macro <name>: actions' definitions
+   - [modes.<name>] holds the keybinds of a binding mode, which are prefixed with '<name>.' otherwise
+   - [monitors.<name>] is the [monitors."<name>"] section
+   - 'include' holds the paths of 'include <path>' lines
+   - [[profiles]] holds the 'if <condition> = <value>' blocks, with the conditions as plain values
+*/
+#[derive(Clone, Debug, Default)]
+pub struct TomlConfig {
+    pub include: Vec<Spanned<String>>,
+    // Keys outside of any table, like the entries before the first section of the legacy format
+    pub top: Vec<TomlEntry>,
+    // [general], [layout], [workspaces], [rules] and [bindings], unknown tables are reported when parsed
+    pub sections: Vec<TomlTable>,
+    pub monitors: Vec<TomlTable>,
+    pub variables: Vec<TomlEntry>,
+    pub macros: Vec<TomlEntry>,
+    pub modes: Vec<TomlTable>,
+    pub profiles: Vec<TomlProfile>,
+}
+
+impl TomlConfig {
+    /*
+       The definitions the legacy format is tokenized into, so both formats share the same parsing
+       and validation. Profiles come last, after everything they may override
+    */
+    fn definitions(&self, file: &Path, definitions: &mut Vec<Definition>) {
+        for path in self.include.iter() {
+            let mut definition = directive(
+                DefinitionKind::INCLUDE,
+                &path.value,
+                path.position.line,
+                path.position.column,
+            );
+            definition.file = file.to_path_buf();
+            definitions.push(definition);
+        }
+        for entry in self.top.iter() {
+            add(
+                definitions,
+                file,
+                DefinitionKind::ENTRY,
+                "",
+                &entry.key.value,
+                entry,
+            );
+        }
+        for table in self.sections.iter() {
+            definitions.push(section(file, &table.name.value, table.name.position));
+            for entry in table.entries.iter() {
+                add(
+                    definitions,
+                    file,
+                    DefinitionKind::ENTRY,
+                    &table.name.value,
+                    &entry.key.value,
+                    entry,
+                );
+            }
+        }
+        for monitor in self.monitors.iter() {
+            let name = format!("{}.\"{}\"", MONITORS_TABLE, monitor.name.value);
+            definitions.push(section(file, &name, monitor.name.position));
+            for entry in monitor.entries.iter() {
+                add(
+                    definitions,
+                    file,
+                    DefinitionKind::ENTRY,
+                    &name,
+                    &entry.key.value,
+                    entry,
+                );
+            }
+        }
+        // Variables, macros and modes belong to the bindings, whether or not there's a [bindings] table
+        let first_binding = self
+            .variables
+            .iter()
+            .chain(self.macros.iter())
+            .map(|entry| entry.key.position)
+            .chain(self.modes.iter().map(|mode| mode.name.position))
+            .next();
+        if let Some(position) = first_binding {
+            definitions.push(section(file, BINDINGS_SECTION, position));
+        }
+        for entry in self.variables.iter() {
+            add(
+                definitions,
+                file,
+                DefinitionKind::VARIABLE,
+                BINDINGS_SECTION,
+                &entry.key.value,
+                entry,
+            );
+        }
+        for entry in self.macros.iter() {
+            let identifier = format!("macro {}", entry.key.value);
+            add(
+                definitions,
+                file,
+                DefinitionKind::ENTRY,
+                BINDINGS_SECTION,
+                &identifier,
+                entry,
+            );
+        }
+        for mode in self.modes.iter() {
+            for entry in mode.entries.iter() {
+                let identifier = format!("{}.{}", mode.name.value, entry.key.value);
+                add(
+                    definitions,
+                    file,
+                    DefinitionKind::ENTRY,
+                    BINDINGS_SECTION,
+                    &identifier,
+                    entry,
+                );
+            }
+        }
+        for profile in self.profiles.iter() {
+            for condition in profile.conditions.iter() {
+                add(
+                    definitions,
+                    file,
+                    DefinitionKind::CONDITION,
+                    "",
+                    &condition.key.value,
+                    condition,
+                );
+            }
+            profile.config.definitions(file, definitions);
+            for _ in profile.conditions.iter() {
+                let mut end = directive(
+                    DefinitionKind::END,
+                    "",
+                    profile.position.line,
+                    profile.position.column,
+                );
+                end.file = file.to_path_buf();
+                definitions.push(end);
+            }
+        }
+    }
+}
+
+fn add(
+    definitions: &mut Vec<Definition>,
+    file: &Path,
+    kind: DefinitionKind,
+    section: &str,
+    identifier: &str,
+    entry: &TomlEntry,
+) {
+    for value in entry.values.iter() {
+        definitions.push(definition(
+            file, kind, section, identifier, &entry.key, value,
+        ));
+    }
+}
+
+fn definition(
+    file: &Path,
+    kind: DefinitionKind,
+    section: &str,
+    identifier: &str,
+    key: &Spanned<String>,
+    value: &Spanned<TomlValue>,
+) -> Definition {
+    // Elements of an array spread over several lines point at their own line
+    let identifier_column = match key.position.line == value.position.line {
+        true => key.position.column,
+        false => value.position.column,
+    };
+    return Definition {
+        kind,
+        file: file.to_path_buf(),
+        section: section.to_string(),
+        identifier: identifier.to_string(),
+        value: value.value.to_string(),
+        line: value.position.line,
+        identifier_column,
+        value_column: value.position.column,
+        conditional: false,
+    };
+}
+
+fn section(file: &Path, name: &str, position: Position) -> Definition {
+    let mut definition = directive(
+        DefinitionKind::SECTION,
+        name,
+        position.line,
+        position.column,
+    );
+    definition.file = file.to_path_buf();
+    return definition;
+}
+
+pub fn tokenize(file: &Path, content: &str) -> (Vec<Definition>, Vec<ConfigError>) {
+    let (config, errors) = deserialize(file, content);
+    let mut definitions: Vec<Definition> = Vec::new();
+    config.definitions(file, &mut definitions);
+    return (definitions, errors);
+}
+
+/*
+   Read fenestra.toml into its tables, keeping the position of every key and value so errors point
+   at them. Every value of the wrong type is reported, so a single pass lists all of them
+*/
+pub fn deserialize(file: &Path, content: &str) -> (TomlConfig, Vec<ConfigError>) {
+    let mut reader = Reader {
+        file,
+        content,
+        errors: Vec::new(),
+    };
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(error) => {
+            reader.error(error.span(), error.message().trim().to_string());
+            return (TomlConfig::default(), reader.errors);
+        }
+    };
+    let config = reader.config(document.as_table());
+    return (config, reader.errors);
+}
+
+struct Reader<'a> {
+    file: &'a Path,
+    content: &'a str,
+    errors: Vec<ConfigError>,
+}
+
+impl Reader<'_> {
+    // The config itself, or the part of a profile which isn't a condition
+    fn config(&mut self, table: &dyn TableLike) -> TomlConfig {
+        let mut config = TomlConfig::default();
+        for (key, item) in table.iter() {
+            let name = self.key(table, key);
+            match key {
+                INCLUDE_KEY => {
+                    for value in self.values(&name, item) {
+                        match value.value {
+                            TomlValue::STRING(path) => config.include.push(Spanned {
+                                value: path,
+                                position: value.position,
+                            }),
+                            _ => self.error_at(value.position, "Expected the path to include"),
+                        }
+                    }
+                }
+                VARIABLES_TABLE => config.variables.extend(self.entries(&name, item)),
+                MACROS_TABLE => config.macros.extend(self.entries(&name, item)),
+                MODES_TABLE => config.modes.extend(self.tables(&name, item)),
+                MONITORS_TABLE => config.monitors.extend(self.tables(&name, item)),
+                PROFILES_TABLE => config.profiles.extend(self.profiles(&name, item)),
+                _ if item.is_table_like() => {
+                    let entries = self.entries(&name, item);
+                    config.sections.push(TomlTable { name, entries });
+                }
+                _ => {
+                    let values = self.values(&name, item);
+                    config.top.push(TomlEntry { key: name, values });
+                }
+            }
+        }
+        return config;
+    }
+
+    // The plain values of a profile are its conditions, each holding a single value
+    fn profiles(&mut self, name: &Spanned<String>, item: &Item) -> Vec<TomlProfile> {
+        let profiles = match item.as_array_of_tables() {
+            Some(profiles) => profiles,
+            None => {
+                self.error(item.span(), format!("Expected [[{}]] tables", name.value));
+                return Vec::new();
+            }
+        };
+        let mut resolved: Vec<TomlProfile> = Vec::new();
+        for profile in profiles.iter() {
+            let mut config = self.config(profile);
+            let conditions: Vec<TomlEntry> = config.top.drain(..).collect();
+            for condition in conditions.iter().filter(|entry| entry.values.len() != 1) {
+                self.error_at(
+                    condition.key.position,
+                    format!("Expected a single value for {}", condition.key.value),
+                );
+            }
+            resolved.push(TomlProfile {
+                position: self.position(profile.span()),
+                conditions,
+                config,
+            });
+        }
+        return resolved;
+    }
+
+    // Tables holding one table each, like [modes.<name>]
+    fn tables(&mut self, name: &Spanned<String>, item: &Item) -> Vec<TomlTable> {
+        let table = match item.as_table_like() {
+            Some(table) => table,
+            None => {
+                self.error(
+                    item.span(),
+                    format!("Expected a table for [{}]", name.value),
+                );
+                return Vec::new();
+            }
+        };
+        let mut tables: Vec<TomlTable> = Vec::new();
+        for (key, item) in table.iter() {
+            let name = self.key(table, key);
+            let entries = self.entries(&name, item);
+            tables.push(TomlTable { name, entries });
+        }
+        return tables;
+    }
+
+    fn entries(&mut self, name: &Spanned<String>, item: &Item) -> Vec<TomlEntry> {
+        let table = match item.as_table_like() {
+            Some(table) => table,
+            None => {
+                self.error(
+                    item.span(),
+                    format!("Expected a table for [{}]", name.value),
+                );
+                return Vec::new();
+            }
+        };
+        let mut entries: Vec<TomlEntry> = Vec::new();
+        for (key, item) in table.iter() {
+            let key = self.key(table, key);
+            let values = self.values(&key, item);
+            entries.push(TomlEntry { key, values });
+        }
+        return entries;
+    }
+
+    fn values(&mut self, key: &Spanned<String>, item: &Item) -> Vec<Spanned<TomlValue>> {
+        let values: Vec<&Value> = match item {
+            Item::Value(Value::Array(array)) => array.iter().collect(),
+            Item::Value(value) => vec![value],
+            _ => {
+                self.error_at(key.position, format!("Unsupported value for {}", key.value));
+                return Vec::new();
+            }
+        };
+        return values
+            .into_iter()
+            .filter_map(|value| self.value(key, value))
+            .collect();
+    }
+
+    fn value(&mut self, key: &Spanned<String>, value: &Value) -> Option<Spanned<TomlValue>> {
+        let position = self.value_position(value.span());
+        let value = match value {
+            Value::String(text) => TomlValue::STRING(text.value().clone()),
+            Value::Integer(number) => TomlValue::INTEGER(*number.value()),
+            Value::Boolean(boolean) => TomlValue::BOOLEAN(*boolean.value()),
+            Value::InlineTable(table) => {
+                let mut parts: Vec<(String, String)> = Vec::new();
+                for (name, part) in table.iter() {
+                    match part.as_str() {
+                        Some(text) => parts.push((name.to_string(), text.to_string())),
+                        None => {
+                            let position = self.value_position(part.span());
+                            self.error_at(position, format!("Expected a string for {}", name));
+                            return None;
+                        }
+                    }
+                }
+                TomlValue::RULE(parts)
+            }
+            _ => {
+                self.error_at(position, format!("Unsupported value for {}", key.value));
+                return None;
+            }
+        };
+        return Some(Spanned { value, position });
+    }
+
+    fn key(&self, table: &dyn TableLike, key: &str) -> Spanned<String> {
+        return Spanned {
+            value: key.to_string(),
+            position: self.position(table.key(key).and_then(|key| key.span())),
+        };
+    }
+
+    // Strings point at their first character rather than their opening quote
+    fn value_position(&self, span: Option<Range<usize>>) -> Position {
+        let mut position = self.position(span.clone());
+        if let Some(rest) = span.and_then(|span| self.content.get(span.start..)) {
+            position.column += match rest {
+                _ if rest.starts_with("\"\"\"") || rest.starts_with("'''") => 3,
+                _ if rest.starts_with(['"', '\'']) => 1,
+                _ => 0,
+            };
+        }
+        return position;
+    }
+
+    // Keys of implicit tables have no span, they point at the start of the file instead
+    fn position(&self, span: Option<Range<usize>>) -> Position {
+        let offset = match span {
+            Some(span) => span.start.min(self.content.len()),
+            None => return Position { line: 1, column: 1 },
+        };
+        let before = &self.content[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .map(|line| line.chars().count() + 1)
+            .unwrap_or(1);
+        return Position { line, column };
+    }
+
+    fn error(&mut self, span: Option<Range<usize>>, message: String) {
+        let position = self.position(span);
+        self.error_at(position, message);
+    }
+
+    fn error_at(&mut self, position: Position, message: impl Into<String>) {
+        self.errors.push(ConfigError::new(
+            self.file,
+            position.line,
+            position.column,
+            message,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+    use crate::config::toml_config::tokenize;
+
+    fn errors(content: &str) -> Vec<(usize, usize, String)> {
+        let machine = Machine::default();
+        return match parse_str(Path::new("fenestra.toml"), content, &machine) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|error| (error.line, error.column, error.message))
+                .collect(),
+        };
+    }
+
+    #[test]
+    fn errors_point_at_the_key_or_value() {
+        let content = "\
+[variables]
+mod = \"WIN + ALT\"
+
+[bindings]
+focus_window_left = \"$mod + WIM\"
+close_window = [
+  \"$mod + q\",
+  \"$mod + BAD\",
+]
+bogus_action = \"$mod + x\"
+ignored = 1.5
+";
+        assert_eq!(
+            errors(content),
+            vec![
+                (5, 29, String::from("Unknown key name 'WIM'")),
+                (8, 11, String::from("Unknown key name 'BAD'")),
+                (10, 1, String::from("Invalid action name bogus_action")),
+                (11, 11, String::from("Unsupported value for ignored")),
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_point_at_their_position() {
+        assert_eq!(
+            errors("[bindings]\nclose_window = \"WIN + q\n"),
+            vec![(2, 24, String::from("invalid basic string"))]
+        );
+    }
+
+    #[test]
+    fn profiles_override_earlier_keybinds() {
+        let content = "\
+[bindings]
+close_window = \"WIN + ALT + q\"
+
+[[profiles]]
+hostname = \"box\"
+[profiles.bindings]
+focus_workspace_1 = \"WIN + ALT + q\"
+";
+        let machine = Machine {
+            hostname: String::from("box"),
+            monitors: Vec::new(),
+        };
+        let config = parse_str(Path::new("fenestra.toml"), content, &machine).unwrap();
+        assert_eq!(config.keybinds.len(), 1);
+        assert_eq!(
+            config.keybinds[0].actions[0].to_string(),
+            "focus_workspace_1"
+        );
+        assert_eq!(config.keybinds[0].line, 7);
+    }
+
+    #[test]
+    fn tables_become_definitions_in_their_section() {
+        let content = "\
+[modes.resize]
+grow_window = \"l\"
+
+[monitors.DISPLAY2]
+gap = 4
+";
+        let (definitions, errors) = tokenize(Path::new("fenestra.toml"), content);
+        assert!(errors.is_empty());
+        let identifiers: Vec<(&str, &str, &str)> = definitions
+            .iter()
+            .map(|definition| {
+                (
+                    definition.section.as_str(),
+                    definition.identifier.as_str(),
+                    definition.value.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            identifiers,
+            vec![
+                ("", "monitors.\"DISPLAY2\"", ""),
+                ("monitors.\"DISPLAY2\"", "gap", "4"),
+                ("", "bindings", ""),
+                ("bindings", "resize.grow_window", "l"),
+            ]
+        );
+    }
+
+    #[test]
+    fn modes_and_variables_parse_without_a_bindings_table() {
+        let machine = Machine::default();
+        let config = parse_str(
+            Path::new("fenestra.toml"),
+            "[modes.resize]\ngrow_window = \"l\"\n",
+            &machine,
+        )
+        .unwrap();
+        let modes: Vec<Option<&str>> = config
+            .keybinds
+            .iter()
+            .map(|keybind| keybind.mode.as_deref())
+            .collect();
+        assert_eq!(modes, vec![Some("resize"), Some("resize")]);
+        // An error like any other rather than a panic
+        assert_eq!(
+            errors("[variables]\nmod = \"WIN + ALT\"\n"),
+            vec![(2, 1, String::from("Variable $mod is never used"))]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::config::lexer::{ConfigError, Token, VARIABLE_PREFIX};
+
+// A 'set $name = keys' declaration, with its keys split into tokens
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub tokens: Vec<Token>,
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    // Declared inside a conditional block, see Definition
+    pub conditional: bool,
+}
+
+/*
+   Declared variables, resolved to plain keys up front. Variables may reference each other in any
+   order, as long as the references don't go round in a cycle. A variable declared again in a later
+   file or in a conditional block that applies replaces the earlier declaration
+*/
+pub struct Variables {
+    declared: HashMap<String, Variable>,
+    resolved: HashMap<String, Vec<Token>>,
+    // Variables which couldn't be resolved, their error has already been reported
+    failed: HashSet<String>,
+    used: HashSet<String>,
+}
+
+impl Variables {
+    pub fn new(declarations: Vec<Variable>) -> (Self, Vec<ConfigError>) {
+        let mut errors: Vec<ConfigError> = Vec::new();
+        let mut variables = Variables {
+            declared: HashMap::new(),
+            resolved: HashMap::new(),
+            failed: HashSet::new(),
+            used: HashSet::new(),
+        };
+        let mut names: Vec<String> = Vec::new();
+        for variable in declarations {
+            if let Some(earlier) = variables.declared.get(&variable.name) {
+                if variable.file != earlier.file || variable.conditional {
+                    variables.declared.insert(variable.name.clone(), variable);
+                    continue;
+                }
+                let earlier_location = match earlier.file == variable.file {
+                    true => format!("line {}", earlier.line),
+                    false => format!("{}:{}", earlier.file.display(), earlier.line),
+                };
+                errors.push(ConfigError::new(
+                    &variable.file,
+                    variable.line,
+                    variable.column,
+                    format!(
+                        "Variable ${} is already declared on {}",
+                        variable.name, earlier_location
+                    ),
+                ));
+                continue;
+            }
+            names.push(variable.name.clone());
+            variables.declared.insert(variable.name.clone(), variable);
+        }
+        for name in names {
+            if variables.failed.contains(&name) {
+                continue;
+            }
+            let mut resolving: Vec<String> = Vec::new();
+            if let Err(error) = variables.resolve(&name, &mut resolving) {
+                errors.extend(error);
+                variables.failed.extend(resolving);
+            }
+        }
+        return (variables, errors);
+    }
+
+    /*
+       Replace the '$name' tokens of a keybind by the keys of the variable, pointing at where the
+       variable was used
+    */
+    pub fn expand(&mut self, tokens: Vec<Token>) -> Result<Vec<Token>, (usize, String)> {
+        let mut expanded: Vec<Token> = Vec::new();
+        for token in tokens {
+            let name = match token.text.strip_prefix(VARIABLE_PREFIX) {
+                Some(name) => name,
+                None => {
+                    expanded.push(token);
+                    continue;
+                }
+            };
+            self.used.insert(name.to_string());
+            if self.failed.contains(name) {
+                continue;
+            }
+            let keys = self
+                .resolved
+                .get(name)
+                .ok_or_else(|| (token.column, format!("Undefined variable ${}", name)))?;
+            expanded.extend(keys.iter().map(|key| Token::new(&key.text, token.column)));
+        }
+        return Ok(expanded);
+    }
+
+    // Declared variables which no keybind or other variable refers to
+    pub fn unused(&self) -> Vec<&Variable> {
+        let mut unused: Vec<&Variable> = self
+            .declared
+            .values()
+            .filter(|variable| !self.used.contains(&variable.name))
+            .collect();
+        unused.sort_by_key(|variable| (variable.file.clone(), variable.line));
+        return unused;
+    }
+
+    // Fails without an error of its own when a referenced variable has already failed
+    fn resolve(
+        &mut self,
+        name: &str,
+        resolving: &mut Vec<String>,
+    ) -> Result<Vec<Token>, Option<ConfigError>> {
+        if let Some(tokens) = self.resolved.get(name) {
+            return Ok(tokens.clone());
+        }
+        let variable = self.declared[name].clone();
+        resolving.push(name.to_string());
+        let mut tokens: Vec<Token> = Vec::new();
+        for token in variable.tokens {
+            let reference = match token.text.strip_prefix(VARIABLE_PREFIX) {
+                Some(reference) => reference.to_string(),
+                None => {
+                    tokens.push(token);
+                    continue;
+                }
+            };
+            self.used.insert(reference.clone());
+            if self.failed.contains(&reference) {
+                return Err(None);
+            }
+            if let Some(start) = resolving.iter().position(|name| *name == reference) {
+                let cycle: Vec<String> = resolving[start..]
+                    .iter()
+                    .chain([&reference])
+                    .map(|name| format!("${}", name))
+                    .collect();
+                return Err(Some(ConfigError::new(
+                    &variable.file,
+                    variable.line,
+                    token.column,
+                    format!(
+                        "Variables reference each other in a cycle: {}",
+                        cycle.join(" -> ")
+                    ),
+                )));
+            }
+            if !self.declared.contains_key(&reference) {
+                return Err(Some(ConfigError::new(
+                    &variable.file,
+                    variable.line,
+                    token.column,
+                    format!("Undefined variable ${}", reference),
+                )));
+            }
+            tokens.extend(self.resolve(&reference, resolving)?);
+        }
+        resolving.pop();
+        self.resolved.insert(name.to_string(), tokens.clone());
+        return Ok(tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::config::lexer::{ConfigError, Token};
+    use crate::config::variables::{Variable, Variables};
+
+    // 'set $name = keys' on the given line, with the keys starting at column 10
+    fn variable(file: &str, line: usize, name: &str, keys: &str) -> Variable {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut column = 10;
+        for key in keys.split(' ') {
+            tokens.push(Token::new(key, column));
+            column += key.len() + 1;
+        }
+        return Variable {
+            name: String::from(name),
+            tokens,
+            file: PathBuf::from(file),
+            line,
+            column: 5,
+            conditional: false,
+        };
+    }
+
+    fn keys(variables: &mut Variables, text: &str) -> Vec<String> {
+        let tokens = text.split(' ').map(|key| Token::new(key, 1)).collect();
+        return variables
+            .expand(tokens)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.text)
+            .collect();
+    }
+
+    fn messages(errors: Vec<ConfigError>) -> Vec<(usize, usize, String)> {
+        return errors
+            .into_iter()
+            .map(|error| (error.line, error.column, error.message))
+            .collect();
+    }
+
+    #[test]
+    fn variables_resolve_in_any_order() {
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 1, "all", "$mod + SHIFT"),
+            variable("a.conf", 2, "mod", "$win + ALT"),
+            variable("a.conf", 3, "win", "WIN"),
+        ]);
+        assert!(errors.is_empty());
+        assert_eq!(
+            keys(&mut variables, "$all + q"),
+            vec!["WIN", "+", "ALT", "+", "SHIFT", "+", "q"]
+        );
+        assert!(variables.unused().is_empty());
+    }
+
+    #[test]
+    fn cycles_are_reported_once() {
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 1, "a", "$b + x"),
+            variable("a.conf", 2, "b", "WIN + $a"),
+        ]);
+        assert_eq!(
+            messages(errors),
+            vec![(
+                2,
+                16,
+                String::from("Variables reference each other in a cycle: $a -> $b -> $a")
+            )]
+        );
+        // Using a variable which failed doesn't repeat the error
+        assert_eq!(keys(&mut variables, "$a + q"), vec!["+", "q"]);
+    }
+
+    #[test]
+    fn undefined_references_point_at_the_declaration() {
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 4, "mod", "WIN + $missing"),
+            variable("a.conf", 5, "other", "$mod"),
+        ]);
+        assert_eq!(
+            messages(errors),
+            vec![(4, 16, String::from("Undefined variable $missing"))]
+        );
+        let undefined = variables.expand(vec![Token::new("$nothing", 7)]);
+        assert_eq!(
+            undefined.err(),
+            Some((7, String::from("Undefined variable $nothing")))
+        );
+    }
+
+    #[test]
+    fn unused_variables_are_listed_by_line() {
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 3, "unused_late", "ALT"),
+            variable("a.conf", 1, "used", "WIN"),
+            variable("a.conf", 2, "unused", "$only_referenced"),
+            variable("a.conf", 4, "only_referenced", "CTRL"),
+        ]);
+        assert!(errors.is_empty());
+        keys(&mut variables, "$used + q");
+        let unused: Vec<&str> = variables
+            .unused()
+            .iter()
+            .map(|variable| variable.name.as_str())
+            .collect();
+        assert_eq!(unused, vec!["unused", "unused_late"]);
+    }
+
+    #[test]
+    fn redeclaring_replaces_across_files_and_in_conditional_blocks() {
+        let mut conditional = variable("a.conf", 3, "gap", "ALT");
+        conditional.conditional = true;
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 1, "mod", "WIN"),
+            variable("b.conf", 1, "mod", "CTRL"),
+            variable("a.conf", 2, "gap", "WIN"),
+            conditional,
+        ]);
+        assert!(errors.is_empty());
+        assert_eq!(keys(&mut variables, "$mod"), vec!["CTRL"]);
+        assert_eq!(keys(&mut variables, "$gap"), vec!["ALT"]);
+    }
+
+    #[test]
+    fn redeclaring_in_the_same_file_is_an_error() {
+        let (mut variables, errors) = Variables::new(vec![
+            variable("a.conf", 1, "mod", "WIN"),
+            variable("a.conf", 6, "mod", "CTRL"),
+        ]);
+        assert_eq!(
+            messages(errors),
+            vec![(
+                6,
+                5,
+                String::from("Variable $mod is already declared on line 1")
+            )]
+        );
+        assert_eq!(keys(&mut variables, "$mod"), vec!["WIN"]);
+    }
+}
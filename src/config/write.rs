@@ -1,17 +1,264 @@
 use std::fs;
 use std::path::Path;
-use std::process::exit;
 
-use log::{error, info};
+use log::info;
 
-pub fn default(config_path: &Path) {
+use crate::config::lexer::ConfigError;
+use crate::config::profile::Machine;
+use crate::config::{migrate, parse};
+use crate::data::action::ACTIONS;
+
+const DEFAULT_CONFIG_FILE: &str = "fenestra.conf";
+
+const HEADER: &str = "\
+# Fenestra config
+#
+# Settings are written as 'setting: value' in the [general], [layout], [workspaces] and [rules]
+# sections, keybinds as 'action: keys' in the [bindings] section, with the keys of a chord joined by '+'.
+# Every action is listed below with what it does, see Configuration.md for the details.
+
+[general]
+# border_width: 2
+
+[bindings]
+# Variables hold keys shared by many keybinds
+set $modifier = WIN + CTRL + ALT
+";
+
+/*
+   Config lines written for each action of the registry, keyed by its identifier. Lines starting
+   with '#' are examples for actions which need a name or program of the user's choosing
+*/
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("focus_window_left", "focus_window_left: $modifier + LEFT"),
+    ("focus_window_down", "focus_window_down: $modifier + DOWN"),
+    ("focus_window_up", "focus_window_up: $modifier + UP"),
+    (
+        "focus_window_right",
+        "focus_window_right: $modifier + RIGHT",
+    ),
+    (
+        "move_window_left",
+        "move_window_left: $modifier + SHIFT + LEFT",
+    ),
+    (
+        "move_window_down",
+        "move_window_down: $modifier + SHIFT + DOWN",
+    ),
+    ("move_window_up", "move_window_up: $modifier + SHIFT + UP"),
+    (
+        "move_window_right",
+        "move_window_right: $modifier + SHIFT + RIGHT",
+    ),
+    ("close_window", "close_window: $modifier + q"),
+    ("grow_window", "grow_window: $modifier + EQUALS"),
+    ("grow_window", "resize.grow_window: RIGHT [repeat]"),
+    ("shrink_window", "shrink_window: $modifier + MINUS"),
+    ("shrink_window", "resize.shrink_window: LEFT [repeat]"),
+    ("toggle_sticky", "toggle_sticky: $modifier + p"),
+    (
+        "toggle_group_stacking",
+        "toggle_group_stacking: $modifier + s",
+    ),
+    (
+        "focus_next_in_stack",
+        "focus_next_in_stack: $modifier + TAB",
+    ),
+    ("focus_workspace_", "focus_workspace_1: $modifier + 1"),
+    ("focus_workspace_", "focus_workspace_2: $modifier + 2"),
+    ("focus_workspace_", "focus_workspace_3: $modifier + 3"),
+    ("focus_workspace_", "focus_workspace_4: $modifier + 4"),
+    ("focus_workspace_", "focus_workspace_5: $modifier + 5"),
+    ("focus_workspace_", "focus_workspace_6: $modifier + 6"),
+    ("focus_workspace_", "focus_workspace_7: $modifier + 7"),
+    ("focus_workspace_", "focus_workspace_8: $modifier + 8"),
+    ("focus_workspace_", "focus_workspace_9: $modifier + 9"),
+    ("focus_workspace_", "focus_workspace_0: $modifier + 0"),
+    (
+        "focus_next_workspace",
+        "focus_next_workspace: $modifier + PAGEDOWN",
+    ),
+    (
+        "focus_previous_workspace",
+        "focus_previous_workspace: $modifier + PAGEUP",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_1: $modifier + SHIFT + 1",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_2: $modifier + SHIFT + 2",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_3: $modifier + SHIFT + 3",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_4: $modifier + SHIFT + 4",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_5: $modifier + SHIFT + 5",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_6: $modifier + SHIFT + 6",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_7: $modifier + SHIFT + 7",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_8: $modifier + SHIFT + 8",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_9: $modifier + SHIFT + 9",
+    ),
+    (
+        "send_to_workspace_",
+        "send_to_workspace_0: $modifier + SHIFT + 0",
+    ),
+    ("drag_window", "drag_window: $modifier + LEFTDRAG"),
+    ("drag_split", "drag_split: $modifier + RIGHTDRAG"),
+    (
+        "move_to_scratchpad",
+        "move_to_scratchpad: $modifier + SHIFT + GRAVE",
+    ),
+    (
+        "move_to_scratchpad_",
+        "# move_to_scratchpad_notes: $modifier + SHIFT + n",
+    ),
+    ("toggle_scratchpad", "toggle_scratchpad: $modifier + GRAVE"),
+    (
+        "toggle_scratchpad_",
+        "# toggle_scratchpad_notes: $modifier + n",
+    ),
+    ("enter_mode_", "enter_mode_resize: $modifier + r"),
+    ("exit_mode", "resize.exit_mode: ESCAPE"),
+    ("exec", "\"exec: wt.exe\": $modifier + ENTER"),
+    (
+        "delay_",
+        "# send_to_workspace_2; delay_250; focus_workspace_2: $modifier + SHIFT + f",
+    ),
+    (
+        "pass_through",
+        "# pass_through: $modifier + q [exe=vmconnect.exe]",
+    ),
+    ("reload_config", "reload_config: $modifier + SHIFT + r"),
+    ("show_keybindings", "show_keybindings: $modifier + SLASH"),
+];
+
+/*
+   The config written when none exists yet. Built from the action registry, so an action without
+   default keys still shows up, commented out
+*/
+pub fn default_config() -> String {
+    let mut config = String::from(HEADER);
+    let mut section = "";
+    for definition in ACTIONS {
+        if definition.section != section {
+            section = definition.section;
+            config += &format!("\n# {} actions\n", section);
+        }
+        config += &format!("\n# {}\n", definition.description);
+        let mut bindings = DEFAULT_BINDINGS
+            .iter()
+            .filter(|(identifier, _)| *identifier == definition.identifier)
+            .peekable();
+        if bindings.peek().is_none() {
+            config += &format!("# {}: <keys>\n", definition.usage());
+        }
+        for (_, line) in bindings {
+            config += line;
+            config += "\n";
+        }
+    }
+    return config;
+}
+
+// Fails when the default config can't be converted for a TOML path, or can't be written
+pub fn default(config_path: &Path) -> Result<(), Vec<ConfigError>> {
     info!(
         "Generating default config at {}",
         config_path.to_str().unwrap()
     );
-    let write_config_result = fs::write(config_path, "Default config content");
-    if write_config_result.is_err() {
-        error!("Failed to write default config to the filesystem");
-        exit(1);
+    // The user config directory may not exist yet
+    if let Some(directory) = config_path.parent() {
+        let _ = fs::create_dir_all(directory);
+    }
+    // A TOML config is asked for by its file name, the default config is converted for it
+    let config = match parse::is_toml(config_path) {
+        true => migrate::to_toml(
+            Path::new(DEFAULT_CONFIG_FILE),
+            &default_config(),
+            &Machine::default(),
+        )?,
+        false => default_config(),
+    };
+    return fs::write(config_path, config).map_err(|write_error| {
+        vec![ConfigError::new(
+            config_path,
+            0,
+            0,
+            format!("Failed to write the default config: {}", write_error),
+        )]
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::migrate::to_toml;
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+    use crate::config::write::{default_config, DEFAULT_BINDINGS, DEFAULT_CONFIG_FILE};
+    use crate::data::action::ACTIONS;
+
+    #[test]
+    fn default_config_parses_without_warnings() {
+        let config = parse_str(
+            Path::new(DEFAULT_CONFIG_FILE),
+            &default_config(),
+            &Machine::default(),
+        )
+        .unwrap_or_else(|errors| panic!("The default config has errors: {:?}", errors));
+        assert!(
+            config.warnings.is_empty(),
+            "The default config has warnings: {:?}",
+            config.warnings
+        );
+        assert!(!config.keybinds.is_empty());
+    }
+
+    // A binding under an identifier missing from the registry would silently be left out
+    #[test]
+    fn default_bindings_belong_to_registered_actions() {
+        for (identifier, line) in DEFAULT_BINDINGS {
+            assert!(
+                ACTIONS
+                    .iter()
+                    .any(|definition| definition.identifier == *identifier),
+                "No action {} for the default binding '{}'",
+                identifier,
+                line
+            );
+            assert!(
+                line.contains(identifier),
+                "The default binding '{}' doesn't use {}",
+                line,
+                identifier
+            );
+        }
+    }
+
+    #[test]
+    fn default_config_migrates_to_toml() {
+        let file = Path::new(DEFAULT_CONFIG_FILE);
+        assert!(to_toml(file, &default_config(), &Machine::default()).is_ok());
     }
 }
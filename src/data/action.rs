@@ -1,10 +1,22 @@
+use crate::actions::config::ReloadConfig;
+use crate::actions::delay::Delay;
+use crate::actions::exec::Exec;
+use crate::actions::groups::{FocusNextInStack, ToggleGroupStacking};
+use crate::actions::keybindings::ShowKeybindings;
+use crate::actions::modes::{EnterMode, ExitMode};
+use crate::actions::pass_through::PassThrough;
 use crate::actions::windows::close::CloseWindow;
+use crate::actions::windows::drag::DragWindow;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 use crate::actions::windows::focus::FocusWindow;
 use crate::actions::windows::r#move::MoveWindow;
-use crate::actions::workspaces::focus::FocusWorkspace;
+use crate::actions::windows::resize::ResizeWindow;
+use crate::actions::windows::sticky::ToggleSticky;
+use crate::actions::workspaces::focus::{CycleWorkspace, FocusWorkspace};
 use crate::actions::workspaces::r#move::MoveToWorkspace;
+use crate::actions::workspaces::scratchpad::{MoveToScratchpad, ToggleScratchpad};
 use crate::state::management::state_manager::StateManager;
 
 pub trait Action {
@@ -15,8 +27,23 @@ pub enum WindowManagerAction {
     FocusWindow(FocusWindow),
     MoveWindow(MoveWindow),
     CloseWindow(CloseWindow),
+    ResizeWindow(ResizeWindow),
+    ToggleSticky(ToggleSticky),
+    DragWindow(DragWindow),
     FocusWorkspace(FocusWorkspace),
+    CycleWorkspace(CycleWorkspace),
     MoveToWorkspace(MoveToWorkspace),
+    MoveToScratchpad(MoveToScratchpad),
+    ToggleScratchpad(ToggleScratchpad),
+    ToggleGroupStacking(ToggleGroupStacking),
+    FocusNextInStack(FocusNextInStack),
+    EnterMode(EnterMode),
+    ExitMode(ExitMode),
+    Exec(Exec),
+    Delay(Delay),
+    PassThrough(PassThrough),
+    ReloadConfig(ReloadConfig),
+    ShowKeybindings(ShowKeybindings),
 }
 
 impl Action for WindowManagerAction {
@@ -25,8 +52,51 @@ impl Action for WindowManagerAction {
             WindowManagerAction::FocusWindow(action) => action.execute(state_manager),
             WindowManagerAction::MoveWindow(action) => action.execute(state_manager),
             WindowManagerAction::CloseWindow(action) => action.execute(state_manager),
+            WindowManagerAction::ResizeWindow(action) => action.execute(state_manager),
+            WindowManagerAction::ToggleSticky(action) => action.execute(state_manager),
+            WindowManagerAction::DragWindow(action) => action.execute(state_manager),
             WindowManagerAction::FocusWorkspace(action) => action.execute(state_manager),
+            WindowManagerAction::CycleWorkspace(action) => action.execute(state_manager),
             WindowManagerAction::MoveToWorkspace(action) => action.execute(state_manager),
+            WindowManagerAction::MoveToScratchpad(action) => action.execute(state_manager),
+            WindowManagerAction::ToggleScratchpad(action) => action.execute(state_manager),
+            WindowManagerAction::ToggleGroupStacking(action) => action.execute(state_manager),
+            WindowManagerAction::FocusNextInStack(action) => action.execute(state_manager),
+            WindowManagerAction::EnterMode(action) => action.execute(state_manager),
+            WindowManagerAction::ExitMode(action) => action.execute(state_manager),
+            WindowManagerAction::Exec(action) => action.execute(state_manager),
+            WindowManagerAction::Delay(action) => action.execute(state_manager),
+            WindowManagerAction::PassThrough(action) => action.execute(state_manager),
+            WindowManagerAction::ReloadConfig(action) => action.execute(state_manager),
+            WindowManagerAction::ShowKeybindings(action) => action.execute(state_manager),
+        }
+    }
+}
+
+// Actions are written back as their config identifier, ex. 'focus_window_left'
+impl Display for WindowManagerAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowManagerAction::FocusWindow(action) => action.fmt(f),
+            WindowManagerAction::MoveWindow(action) => action.fmt(f),
+            WindowManagerAction::CloseWindow(action) => action.fmt(f),
+            WindowManagerAction::ResizeWindow(action) => action.fmt(f),
+            WindowManagerAction::ToggleSticky(action) => action.fmt(f),
+            WindowManagerAction::DragWindow(action) => action.fmt(f),
+            WindowManagerAction::FocusWorkspace(action) => action.fmt(f),
+            WindowManagerAction::CycleWorkspace(action) => action.fmt(f),
+            WindowManagerAction::MoveToWorkspace(action) => action.fmt(f),
+            WindowManagerAction::MoveToScratchpad(action) => action.fmt(f),
+            WindowManagerAction::ToggleScratchpad(action) => action.fmt(f),
+            WindowManagerAction::ToggleGroupStacking(action) => action.fmt(f),
+            WindowManagerAction::FocusNextInStack(action) => action.fmt(f),
+            WindowManagerAction::EnterMode(action) => action.fmt(f),
+            WindowManagerAction::ExitMode(action) => action.fmt(f),
+            WindowManagerAction::Exec(action) => action.fmt(f),
+            WindowManagerAction::Delay(action) => action.fmt(f),
+            WindowManagerAction::PassThrough(action) => action.fmt(f),
+            WindowManagerAction::ReloadConfig(action) => action.fmt(f),
+            WindowManagerAction::ShowKeybindings(action) => action.fmt(f),
         }
     }
 }
@@ -34,28 +104,266 @@ impl Action for WindowManagerAction {
 impl FromStr for WindowManagerAction {
     type Err = ();
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let action: String = input.to_ascii_uppercase();
-        if action.contains("FOCUS_WINDOW_") {
-            return Ok(WindowManagerAction::FocusWindow(
-                FocusWindow::from_str(action.as_str()).unwrap(),
-            ));
-        } else if action.contains("MOVE_WINDOW_") {
-            return Ok(WindowManagerAction::MoveWindow(
-                MoveWindow::from_str(action.as_str()).unwrap(),
-            ));
-        } else if action.eq("CLOSE_WINDOW") {
-            return Ok(WindowManagerAction::CloseWindow(
-                CloseWindow::from_str(action.as_str()).unwrap(),
-            ));
-        } else if action.contains("FOCUS_WORKSPACE_") {
-            return Ok(WindowManagerAction::FocusWorkspace(
-                FocusWorkspace::from_str(action.as_str()).unwrap(),
-            ));
-        } else if action.contains("SEND_TO_WORKSPACE_") {
-            return Ok(WindowManagerAction::MoveToWorkspace(
-                MoveToWorkspace::from_str(action.as_str()).unwrap(),
-            ));
-        }
-        return Err(());
+        let input = input.trim();
+        return match ACTIONS.iter().find(|definition| definition.matches(input)) {
+            Some(definition) => (definition.parse)(input),
+            None => Err(()),
+        };
+    }
+}
+
+/*
+   An action identifier the config accepts, along with what it does. The parser looks actions up
+   here, and the default config and docs are generated from the same list, so an action is added
+   by adding its definition
+*/
+pub struct ActionDefinition {
+    // The identifier, or its prefix when the action takes a parameter
+    pub identifier: &'static str,
+    // Describes the parameter following the prefix, e.g. <n> for a workspace number
+    pub parameter: Option<&'static str>,
+    pub section: &'static str,
+    pub description: &'static str,
+    // Each action parses its own parameters, and keeps or ignores case as it needs
+    parse: fn(&str) -> Result<WindowManagerAction, ()>,
+}
+
+impl ActionDefinition {
+    pub fn matches(&self, action: &str) -> bool {
+        let action = action.trim();
+        return match self.parameter {
+            Some(_) => action
+                .get(..self.identifier.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(self.identifier)),
+            None => action.eq_ignore_ascii_case(self.identifier),
+        };
+    }
+
+    // How the identifier is written in the config, e.g. focus_workspace_<n>
+    pub fn usage(&self) -> String {
+        return format!("{}{}", self.identifier, self.parameter.unwrap_or_default());
     }
 }
+
+pub const ACTIONS: &[ActionDefinition] = &[
+    ActionDefinition {
+        identifier: "focus_window_left",
+        parameter: None,
+        section: "Window",
+        description: "Focus the window left of the current one",
+        parse: |input| FocusWindow::from_str(input).map(WindowManagerAction::FocusWindow),
+    },
+    ActionDefinition {
+        identifier: "focus_window_down",
+        parameter: None,
+        section: "Window",
+        description: "Focus the window below the current one",
+        parse: |input| FocusWindow::from_str(input).map(WindowManagerAction::FocusWindow),
+    },
+    ActionDefinition {
+        identifier: "focus_window_up",
+        parameter: None,
+        section: "Window",
+        description: "Focus the window above the current one",
+        parse: |input| FocusWindow::from_str(input).map(WindowManagerAction::FocusWindow),
+    },
+    ActionDefinition {
+        identifier: "focus_window_right",
+        parameter: None,
+        section: "Window",
+        description: "Focus the window right of the current one",
+        parse: |input| FocusWindow::from_str(input).map(WindowManagerAction::FocusWindow),
+    },
+    ActionDefinition {
+        identifier: "move_window_left",
+        parameter: None,
+        section: "Window",
+        description: "Move the current window left",
+        parse: |input| MoveWindow::from_str(input).map(WindowManagerAction::MoveWindow),
+    },
+    ActionDefinition {
+        identifier: "move_window_down",
+        parameter: None,
+        section: "Window",
+        description: "Move the current window down",
+        parse: |input| MoveWindow::from_str(input).map(WindowManagerAction::MoveWindow),
+    },
+    ActionDefinition {
+        identifier: "move_window_up",
+        parameter: None,
+        section: "Window",
+        description: "Move the current window up",
+        parse: |input| MoveWindow::from_str(input).map(WindowManagerAction::MoveWindow),
+    },
+    ActionDefinition {
+        identifier: "move_window_right",
+        parameter: None,
+        section: "Window",
+        description: "Move the current window right",
+        parse: |input| MoveWindow::from_str(input).map(WindowManagerAction::MoveWindow),
+    },
+    ActionDefinition {
+        identifier: "close_window",
+        parameter: None,
+        section: "Window",
+        description: "Close the current window",
+        parse: |input| CloseWindow::from_str(input).map(WindowManagerAction::CloseWindow),
+    },
+    ActionDefinition {
+        identifier: "grow_window",
+        parameter: None,
+        section: "Window",
+        description: "Grow the current window's share of its group",
+        parse: |input| ResizeWindow::from_str(input).map(WindowManagerAction::ResizeWindow),
+    },
+    ActionDefinition {
+        identifier: "shrink_window",
+        parameter: None,
+        section: "Window",
+        description: "Shrink the current window's share of its group",
+        parse: |input| ResizeWindow::from_str(input).map(WindowManagerAction::ResizeWindow),
+    },
+    ActionDefinition {
+        identifier: "toggle_sticky",
+        parameter: None,
+        section: "Window",
+        description: "Pin the current window to every workspace on its monitor, or unpin it",
+        parse: |input| ToggleSticky::from_str(input).map(WindowManagerAction::ToggleSticky),
+    },
+    ActionDefinition {
+        identifier: "toggle_group_stacking",
+        parameter: None,
+        section: "Group",
+        description: "Switch the current group between splitting its windows and stacking them",
+        parse: |input| {
+            ToggleGroupStacking::from_str(input).map(WindowManagerAction::ToggleGroupStacking)
+        },
+    },
+    ActionDefinition {
+        identifier: "focus_next_in_stack",
+        parameter: None,
+        section: "Group",
+        description:
+            "Focus the next window in the current group, bringing it to the top of the stack",
+        parse: |input| FocusNextInStack::from_str(input).map(WindowManagerAction::FocusNextInStack),
+    },
+    ActionDefinition {
+        identifier: "focus_workspace_",
+        parameter: Some("<n>"),
+        section: "Workspace",
+        description: "Focus workspace n, 0 being workspace 10",
+        parse: |input| FocusWorkspace::from_str(input).map(WindowManagerAction::FocusWorkspace),
+    },
+    ActionDefinition {
+        identifier: "focus_next_workspace",
+        parameter: None,
+        section: "Workspace",
+        description: "Focus the next workspace of the current monitor",
+        parse: |input| CycleWorkspace::from_str(input).map(WindowManagerAction::CycleWorkspace),
+    },
+    ActionDefinition {
+        identifier: "focus_previous_workspace",
+        parameter: None,
+        section: "Workspace",
+        description: "Focus the previous workspace of the current monitor",
+        parse: |input| CycleWorkspace::from_str(input).map(WindowManagerAction::CycleWorkspace),
+    },
+    ActionDefinition {
+        identifier: "send_to_workspace_",
+        parameter: Some("<n>"),
+        section: "Workspace",
+        description: "Send the current window to workspace n, 0 being workspace 10",
+        parse: |input| MoveToWorkspace::from_str(input).map(WindowManagerAction::MoveToWorkspace),
+    },
+    ActionDefinition {
+        identifier: "drag_window",
+        parameter: None,
+        section: "Mouse",
+        description: "Drag a tiled window onto another one to swap their places",
+        parse: |input| DragWindow::from_str(input).map(WindowManagerAction::DragWindow),
+    },
+    ActionDefinition {
+        identifier: "drag_split",
+        parameter: None,
+        section: "Mouse",
+        description: "Drag to move the edge between a tiled window and its neighbour",
+        parse: |input| DragWindow::from_str(input).map(WindowManagerAction::DragWindow),
+    },
+    ActionDefinition {
+        identifier: "move_to_scratchpad",
+        parameter: None,
+        section: "Scratchpad",
+        description: "Hide the current window in the scratchpad",
+        parse: |input| MoveToScratchpad::from_str(input).map(WindowManagerAction::MoveToScratchpad),
+    },
+    ActionDefinition {
+        identifier: "move_to_scratchpad_",
+        parameter: Some("<name>"),
+        section: "Scratchpad",
+        description: "Hide the current window in the scratchpad under the given name",
+        parse: |input| MoveToScratchpad::from_str(input).map(WindowManagerAction::MoveToScratchpad),
+    },
+    ActionDefinition {
+        identifier: "toggle_scratchpad",
+        parameter: None,
+        section: "Scratchpad",
+        description: "Show the most recent scratchpad window, or hide the shown one",
+        parse: |input| ToggleScratchpad::from_str(input).map(WindowManagerAction::ToggleScratchpad),
+    },
+    ActionDefinition {
+        identifier: "toggle_scratchpad_",
+        parameter: Some("<name>"),
+        section: "Scratchpad",
+        description: "Show or hide the scratchpad window stored under the given name",
+        parse: |input| ToggleScratchpad::from_str(input).map(WindowManagerAction::ToggleScratchpad),
+    },
+    ActionDefinition {
+        identifier: "enter_mode_",
+        parameter: Some("<name>"),
+        section: "Binding mode",
+        description: "Switch to the keybinds prefixed with '<name>.'",
+        parse: |input| EnterMode::from_str(input).map(WindowManagerAction::EnterMode),
+    },
+    ActionDefinition {
+        identifier: "exit_mode",
+        parameter: None,
+        section: "Binding mode",
+        description: "Return to the global keybinds",
+        parse: |input| ExitMode::from_str(input).map(WindowManagerAction::ExitMode),
+    },
+    ActionDefinition {
+        identifier: "exec",
+        parameter: Some(": <command line>"),
+        section: "Program",
+        description: "Start a program, arguments containing spaces are wrapped in double quotes",
+        parse: |input| Exec::from_str(input).map(WindowManagerAction::Exec),
+    },
+    ActionDefinition {
+        identifier: "delay_",
+        parameter: Some("<milliseconds>"),
+        section: "Action list",
+        description: "Wait before running the next action of the keybind",
+        parse: |input| Delay::from_str(input).map(WindowManagerAction::Delay),
+    },
+    ActionDefinition {
+        identifier: "pass_through",
+        parameter: None,
+        section: "Application",
+        description: "Leave the keys to the application instead of handling them",
+        parse: |input| PassThrough::from_str(input).map(WindowManagerAction::PassThrough),
+    },
+    ActionDefinition {
+        identifier: "reload_config",
+        parameter: None,
+        section: "Config",
+        description: "Read the config again and apply it without restarting",
+        parse: |input| ReloadConfig::from_str(input).map(WindowManagerAction::ReloadConfig),
+    },
+    ActionDefinition {
+        identifier: "show_keybindings",
+        parameter: None,
+        section: "Config",
+        description: "Show every keybind of the config on screen, or hide them again",
+        parse: |input| ShowKeybindings::from_str(input).map(WindowManagerAction::ShowKeybindings),
+    },
+];
@@ -0,0 +1,196 @@
+use windows::Win32::Foundation::RECT;
+
+use crate::data::config::Settings;
+
+// DPI at which the border width is given, 100% scaling
+pub const DEFAULT_DPI: u32 = 96;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BorderStyle {
+    pub width: i32,
+    pub active_color: u32,
+    pub inactive_color: u32,
+    pub rounded: bool,
+}
+
+impl From<&Settings> for BorderStyle {
+    fn from(settings: &Settings) -> Self {
+        BorderStyle {
+            width: settings.border_width as i32,
+            active_color: settings.border_active_color,
+            inactive_color: settings.border_inactive_color,
+            rounded: settings.border_rounded,
+        }
+    }
+}
+
+impl BorderStyle {
+    pub fn enabled(&self) -> bool {
+        self.width > 0
+    }
+
+    pub fn color(&self, active: bool) -> u32 {
+        match active {
+            true => self.active_color,
+            false => self.inactive_color,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BorderGeometry {
+    // Bounds of the overlay window
+    pub outer: RECT,
+    // Transparent area left uncovered by the border
+    pub inner: RECT,
+    // Border width in physical pixels
+    pub width: i32,
+}
+
+/*
+   The border is drawn over the outer edge of the visible window frame so it never overlaps
+   neighbouring tiles, whatever the gap between them. The visible frame is the tiled rect, narrowed
+   down to the DWM frame bounds (shadow_rect) when those are known, which also drops the part of a
+   maximized window hanging off the monitor. The width is scaled from 96 DPI to the window's DPI
+*/
+pub fn border_geometry(
+    rect: &RECT,
+    shadow_rect: &RECT,
+    width: i32,
+    dpi: u32,
+) -> Option<BorderGeometry> {
+    // Windows report a DPI of 0 when it couldn't be determined
+    let dpi = match dpi {
+        0 => DEFAULT_DPI,
+        dpi => dpi,
+    };
+    let width = (width as i64 * dpi as i64 + DEFAULT_DPI as i64 / 2) / DEFAULT_DPI as i64;
+    let width = width as i32;
+    if width <= 0 || is_empty(rect) {
+        return None;
+    }
+    let mut outer = *rect;
+    if !is_empty(shadow_rect) {
+        outer = RECT {
+            left: rect.left.max(shadow_rect.left),
+            top: rect.top.max(shadow_rect.top),
+            right: rect.right.min(shadow_rect.right),
+            bottom: rect.bottom.min(shadow_rect.bottom),
+        };
+        if is_empty(&outer) {
+            outer = *rect;
+        }
+    }
+    let inner = RECT {
+        left: outer.left + width,
+        top: outer.top + width,
+        right: outer.right - width,
+        bottom: outer.bottom - width,
+    };
+    if is_empty(&inner) {
+        return None;
+    }
+    return Some(BorderGeometry {
+        outer,
+        inner,
+        width,
+    });
+}
+
+fn is_empty(rect: &RECT) -> bool {
+    rect.right <= rect.left || rect.bottom <= rect.top
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::RECT;
+
+    use crate::data::border::{border_geometry, DEFAULT_DPI};
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        return RECT {
+            left,
+            top,
+            right,
+            bottom,
+        };
+    }
+
+    #[test]
+    fn border_covers_the_edge_of_the_visible_frame() {
+        // The window rect includes the invisible resize borders around the DWM frame
+        let geometry = border_geometry(
+            &rect(-7, 0, 967, 1047),
+            &rect(0, 0, 960, 1040),
+            2,
+            DEFAULT_DPI,
+        )
+        .unwrap();
+        assert_eq!(geometry.outer, rect(0, 0, 960, 1040));
+        assert_eq!(geometry.inner, rect(2, 2, 958, 1038));
+    }
+
+    #[test]
+    fn width_is_scaled_to_the_window_dpi() {
+        let area = rect(0, 0, 960, 1040);
+        assert_eq!(border_geometry(&area, &area, 2, 144).unwrap().width, 3);
+        assert_eq!(border_geometry(&area, &area, 3, 192).unwrap().width, 6);
+        assert_eq!(border_geometry(&area, &area, 2, 120).unwrap().width, 3);
+        let geometry = border_geometry(&area, &area, 2, 192).unwrap();
+        assert_eq!(geometry.inner, rect(4, 4, 956, 1036));
+    }
+
+    #[test]
+    fn maximized_windows_are_cut_to_the_visible_frame() {
+        // A maximized window hangs off the monitor by its resize borders
+        let geometry = border_geometry(
+            &rect(-8, -8, 1928, 1048),
+            &rect(0, 0, 1920, 1040),
+            2,
+            DEFAULT_DPI,
+        )
+        .unwrap();
+        assert_eq!(geometry.outer, rect(0, 0, 1920, 1040));
+    }
+
+    #[test]
+    fn a_border_wider_than_the_gap_stays_inside_its_window() {
+        // Two tiles 4 pixels apart, each with a 10 pixel border
+        let left = border_geometry(
+            &rect(0, 0, 958, 1040),
+            &rect(0, 0, 958, 1040),
+            10,
+            DEFAULT_DPI,
+        )
+        .unwrap();
+        let right = border_geometry(
+            &rect(962, 0, 1920, 1040),
+            &rect(962, 0, 1920, 1040),
+            10,
+            DEFAULT_DPI,
+        )
+        .unwrap();
+        assert!(left.outer.right <= right.outer.left);
+        assert_eq!(left.inner.right, 948);
+    }
+
+    #[test]
+    fn no_border_when_it_would_fill_the_window() {
+        let small = rect(0, 0, 10, 10);
+        assert!(border_geometry(&small, &small, 5, DEFAULT_DPI).is_none());
+        assert!(border_geometry(&small, &small, 0, DEFAULT_DPI).is_none());
+        assert!(border_geometry(&rect(0, 0, 0, 0), &small, 2, DEFAULT_DPI).is_none());
+    }
+
+    #[test]
+    fn disjoint_frame_bounds_fall_back_to_the_window_rect() {
+        let geometry = border_geometry(
+            &rect(0, 0, 100, 100),
+            &rect(200, 200, 300, 300),
+            1,
+            DEFAULT_DPI,
+        )
+        .unwrap();
+        assert_eq!(geometry.outer, rect(0, 0, 100, 100));
+    }
+}
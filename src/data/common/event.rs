@@ -1,4 +1,4 @@
-use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::Foundation::{HWND, LPARAM, POINT, WPARAM};
 use windows::Win32::UI::WindowsAndMessaging::WM_APP;
 
 pub struct Event {
@@ -9,18 +9,33 @@ pub struct Event {
 
 pub const KEY_EVENT: u32 = WM_APP + 2;
 pub const WINDOW_EVENT: u32 = WM_APP + 3;
+pub const MOUSE_EVENT: u32 = WM_APP + 4;
+pub const CONFIG_EVENT: u32 = WM_APP + 5;
+pub const KEYBINDINGS_EVENT: u32 = WM_APP + 6;
 pub const MINIMIZE: usize = 0;
 pub const RESTORE: usize = 1;
 pub const MOVE_SIZE: usize = 2;
 pub const FOCUS: usize = 3;
 pub const CREATE: usize = 4;
 pub const DESTROY: usize = 5;
+pub const TITLE_CHANGE: usize = 6;
 impl Event {
-    pub fn key_event(key_code: isize, wparam: WPARAM) -> Event {
+    /*
+       The scan code is packed above the virtual key code, and the character the key types above
+       both, 0 when it types none. The hook looks the character up once, as it depends on the
+       keyboard layout of the foreground window at the time of the key press
+    */
+    pub fn key_event(
+        key_code: isize,
+        scan_code: isize,
+        character: Option<char>,
+        wparam: WPARAM,
+    ) -> Event {
+        let character = character.map_or(0, |character| character as isize);
         Event {
             message: KEY_EVENT,
             wparam,
-            lparam: LPARAM(key_code),
+            lparam: LPARAM(key_code | (scan_code << 16) | (character << 32)),
         }
     }
 
@@ -63,4 +78,72 @@ impl Event {
             lparam: LPARAM(hwnd.0),
         }
     }
+
+    pub fn title_change(hwnd: HWND) -> Event {
+        Event {
+            message: WINDOW_EVENT,
+            wparam: WPARAM(TITLE_CHANGE),
+            lparam: LPARAM(hwnd.0),
+        }
+    }
+
+    // Asks the main loop to re-read the config file
+    pub fn reload_config() -> Event {
+        Event {
+            message: CONFIG_EVENT,
+            wparam: WPARAM(0),
+            lparam: LPARAM(0),
+        }
+    }
+
+    // Asks the main loop to show or hide the keybinds on screen
+    pub fn show_keybindings() -> Event {
+        Event {
+            message: KEYBINDINGS_EVENT,
+            wparam: WPARAM(0),
+            lparam: LPARAM(0),
+        }
+    }
+
+    pub fn mouse_move(point: POINT) -> Event {
+        Event {
+            message: MOUSE_EVENT,
+            wparam: WPARAM(0),
+            lparam: LPARAM(
+                ((point.y as i16 as u16 as isize) << 16) | point.x as i16 as u16 as isize,
+            ),
+        }
+    }
+}
+
+pub fn point_from_lparam(lparam: LPARAM) -> POINT {
+    POINT {
+        x: (lparam.0 & 0xFFFF) as u16 as i16 as i32,
+        y: ((lparam.0 >> 16) & 0xFFFF) as u16 as i16 as i32,
+    }
+}
+
+// Returns the virtual key code, scan code and typed character of a key event
+pub fn key_from_lparam(lparam: LPARAM) -> (i32, i32, Option<char>) {
+    let key_code = (lparam.0 & 0xFFFF) as i32;
+    let scan_code = ((lparam.0 >> 16) & 0xFFFF) as i32;
+    let character = char::from_u32((lparam.0 >> 32) as u32).filter(|character| *character != '\0');
+    return (key_code, scan_code, character);
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::WPARAM;
+
+    use crate::data::common::event::{key_from_lparam, Event};
+
+    #[test]
+    fn key_events_carry_their_character() {
+        let event = Event::key_event(0x51, 0x10, Some('q'), WPARAM(0));
+        assert_eq!(key_from_lparam(event.lparam), (0x51, 0x10, Some('q')));
+        let event = Event::key_event(0xDB, 0x1A, Some('ü'), WPARAM(0));
+        assert_eq!(key_from_lparam(event.lparam), (0xDB, 0x1A, Some('ü')));
+        let event = Event::key_event(0x25, 0xE04B, None, WPARAM(0));
+        assert_eq!(key_from_lparam(event.lparam), (0x25, 0xE04B, None));
+    }
 }
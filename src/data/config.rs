@@ -0,0 +1,428 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::config::lexer::ConfigError;
+use crate::data::common::axis::Axis;
+use crate::data::key::Keybind;
+use crate::data::window::WindowRule;
+
+// Workspaces per monitor, the workspace actions go up to 10
+pub const MAX_WORKSPACES: usize = 10;
+
+pub struct Config {
+    pub keybinds: Vec<Keybind>,
+    pub settings: Settings,
+    // Problems which don't stop the config from being used, like keybinds shadowing each other
+    pub warnings: Vec<ConfigError>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub focus_follows_mouse: bool,
+    pub focus_follows_mouse_delay: u32,
+    pub mouse_follows_focus: bool,
+    pub border_width: u32,
+    pub border_active_color: u32,
+    pub border_inactive_color: u32,
+    pub border_rounded: bool,
+    pub sticky_windows: Vec<String>,
+    pub sticky_tiled: bool,
+    pub scratchpad_rules: Vec<(String, String)>,
+    pub scratchpad_width: u32,
+    pub scratchpad_height: u32,
+    pub stack_tabs: bool,
+    pub stack_tab_height: u32,
+    pub sequence_timeout: u32,
+    pub watch_config: bool,
+    // Layout of every monitor, unless overridden by its [monitors."<name>"] section
+    pub layout: LayoutSettings,
+    pub monitors: Vec<(String, LayoutSettings)>,
+    // Windows which are never managed
+    pub ignore_rules: Vec<WindowRule>,
+    // Not a setting of its own, set once a keybind uses the mouse so the mouse hook gets installed
+    pub mouse_bindings: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            focus_follows_mouse: false,
+            focus_follows_mouse_delay: 250,
+            mouse_follows_focus: false,
+            border_width: 0,
+            border_active_color: 0x4C7899,
+            border_inactive_color: 0x333333,
+            border_rounded: false,
+            sticky_windows: Vec::new(),
+            sticky_tiled: false,
+            scratchpad_rules: Vec::new(),
+            scratchpad_width: 60,
+            scratchpad_height: 60,
+            stack_tabs: false,
+            stack_tab_height: 24,
+            sequence_timeout: 1000,
+            watch_config: false,
+            layout: LayoutSettings::default(),
+            monitors: Vec::new(),
+            ignore_rules: Vec::new(),
+            mouse_bindings: false,
+        }
+    }
+}
+
+// Layout settings, each one left unset falls back to the global layout and then to its default
+#[derive(Clone, Debug, Default)]
+pub struct LayoutSettings {
+    // Picked by the monitor orientation when not set, side by side on landscape monitors
+    pub split_axis: Option<Axis>,
+    // Space kept free at the bottom of the monitor, the height of the taskbar when not set
+    pub taskbar_offset: Option<u32>,
+    pub workspace_count: Option<usize>,
+}
+
+impl LayoutSettings {
+    // Fill in the settings left unset from another layout
+    fn or(&self, fallback: &LayoutSettings) -> LayoutSettings {
+        LayoutSettings {
+            split_axis: self.split_axis.or(fallback.split_axis),
+            taskbar_offset: self.taskbar_offset.or(fallback.taskbar_offset),
+            workspace_count: self.workspace_count.or(fallback.workspace_count),
+        }
+    }
+
+    fn apply(&mut self, identifier: &str, value: &str) -> Result<(), String> {
+        match identifier.to_ascii_lowercase().as_str() {
+            "split_axis" => {
+                self.split_axis = match value.to_ascii_lowercase().as_str() {
+                    "auto" => None,
+                    _ => Some(Axis::from_str(value).map_err(|_| {
+                        format!(
+                        "Invalid axis '{}' for setting {}, expected auto, horizontal or vertical",
+                        value, identifier
+                    )
+                    })?),
+                }
+            }
+            "taskbar_offset" => {
+                self.taskbar_offset = match value.to_ascii_lowercase().as_str() {
+                    "auto" => None,
+                    _ => Some(parse_number(identifier, value, "offset")?),
+                }
+            }
+            "workspace_count" => {
+                let count = parse_number(identifier, value, "count")? as usize;
+                if !(1..=MAX_WORKSPACES).contains(&count) {
+                    return Err(format!(
+                        "Setting {} must be between 1 and {}",
+                        identifier, MAX_WORKSPACES
+                    ));
+                }
+                self.workspace_count = Some(count);
+            }
+            _ => return Err(format!("Unknown setting {}", identifier)),
+        }
+        return Ok(());
+    }
+}
+
+/*
+   The sections of the config. Entries before the first section header may be settings or keybinds,
+   as configs were written before there were sections
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Section {
+    TOP,
+    GENERAL,
+    LAYOUT,
+    WORKSPACES,
+    MONITOR(String),
+    RULES,
+    BINDINGS,
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    // Monitor sections are written as [monitors."DISPLAY2"], the quotes being optional
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if let Some((prefix, name)) = input.split_once('.') {
+            let name = name.trim().trim_matches('"');
+            if prefix.trim().eq_ignore_ascii_case("monitors") && !name.is_empty() {
+                return Ok(Section::MONITOR(name.to_string()));
+            }
+        }
+        match input.to_ascii_lowercase().as_str() {
+            "general" => Ok(Section::GENERAL),
+            "layout" => Ok(Section::LAYOUT),
+            "workspaces" => Ok(Section::WORKSPACES),
+            "rules" => Ok(Section::RULES),
+            "bindings" => Ok(Section::BINDINGS),
+            _ => Err(format!("Unknown section [{}]", input)),
+        }
+    }
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Section::TOP => write!(f, "top of the config"),
+            Section::GENERAL => write!(f, "[general]"),
+            Section::LAYOUT => write!(f, "[layout]"),
+            Section::WORKSPACES => write!(f, "[workspaces]"),
+            Section::MONITOR(name) => write!(f, "[monitors.\"{}\"]", name),
+            Section::RULES => write!(f, "[rules]"),
+            Section::BINDINGS => write!(f, "[bindings]"),
+        }
+    }
+}
+
+// Every setting along with the section it belongs in
+pub const SETTINGS: &[(&str, Section)] = &[
+    ("focus_follows_mouse", Section::GENERAL),
+    ("focus_follows_mouse_delay", Section::GENERAL),
+    ("mouse_follows_focus", Section::GENERAL),
+    ("border_width", Section::GENERAL),
+    ("border_active_color", Section::GENERAL),
+    ("border_inactive_color", Section::GENERAL),
+    ("border_rounded", Section::GENERAL),
+    ("stack_tabs", Section::GENERAL),
+    ("stack_tab_height", Section::GENERAL),
+    ("scratchpad_width", Section::GENERAL),
+    ("scratchpad_height", Section::GENERAL),
+    ("sequence_timeout", Section::GENERAL),
+    ("watch_config", Section::GENERAL),
+    ("split_axis", Section::LAYOUT),
+    ("taskbar_offset", Section::LAYOUT),
+    ("workspace_count", Section::WORKSPACES),
+    ("sticky_window", Section::RULES),
+    ("sticky_tiled", Section::RULES),
+    ("scratchpad_window", Section::RULES),
+    ("ignore_window", Section::RULES),
+];
+
+// Settings a [monitors."<name>"] section may override
+const MONITOR_SETTINGS: &[&str] = &["split_axis", "taskbar_offset", "workspace_count"];
+
+// Windows with a title like the Settings app are left alone, unless the config lists its own windows to ignore
+const DEFAULT_IGNORED_TITLE: &str = "settings";
+
+impl Settings {
+    // Whether an entry of the section is a setting, rather than a keybind
+    pub fn is_setting(section: &Section, identifier: &str) -> bool {
+        match section {
+            Section::TOP | Section::BINDINGS => setting_section(identifier).is_some(),
+            _ => true,
+        }
+    }
+
+    // Apply a single config entry, the current value is kept when it cannot be parsed
+    pub fn apply(
+        &mut self,
+        section: &Section,
+        identifier: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        let value = value.trim();
+        if let Section::MONITOR(name) = section {
+            if !MONITOR_SETTINGS.contains(&identifier.to_ascii_lowercase().as_str()) {
+                return Err(format!("Unknown monitor setting {}", identifier));
+            }
+            let index = match self
+                .monitors
+                .iter()
+                .position(|(monitor, _)| monitor.eq_ignore_ascii_case(name))
+            {
+                Some(index) => index,
+                None => {
+                    self.monitors
+                        .push((name.clone(), LayoutSettings::default()));
+                    self.monitors.len() - 1
+                }
+            };
+            return self.monitors[index].1.apply(identifier, value);
+        }
+        match setting_section(identifier) {
+            None => {
+                return Err(format!(
+                    "Unknown setting {}, keybinds go in the [bindings] section",
+                    identifier
+                ))
+            }
+            Some(expected) if *section != Section::TOP && *section != expected => {
+                return Err(format!(
+                    "Setting {} belongs in the {} section",
+                    identifier, expected
+                ))
+            }
+            Some(_) => {}
+        }
+        match identifier.to_ascii_lowercase().as_str() {
+            "focus_follows_mouse" => self.focus_follows_mouse = parse_bool(identifier, value)?,
+            "focus_follows_mouse_delay" => {
+                self.focus_follows_mouse_delay = parse_number(identifier, value, "delay")?
+            }
+            "mouse_follows_focus" => self.mouse_follows_focus = parse_bool(identifier, value)?,
+            "border_width" => self.border_width = parse_number(identifier, value, "width")?,
+            "border_active_color" => self.border_active_color = parse_color(identifier, value)?,
+            "border_inactive_color" => self.border_inactive_color = parse_color(identifier, value)?,
+            "border_rounded" => self.border_rounded = parse_bool(identifier, value)?,
+            // May be repeated, each entry adds another window title to match
+            "sticky_window" => self.sticky_windows.push(value.to_string()),
+            "sticky_tiled" => self.sticky_tiled = parse_bool(identifier, value)?,
+            // May be repeated, written as <scratchpad name> = <window title>
+            "scratchpad_window" => match value.split_once('=') {
+                Some((name, title)) => self
+                    .scratchpad_rules
+                    .push((name.trim().to_ascii_lowercase(), title.trim().to_string())),
+                None => {
+                    return Err(format!(
+                        "Expected '<name> = <title>' for setting {}",
+                        identifier
+                    ))
+                }
+            },
+            // May be repeated, written like the application options of keybinds
+            "ignore_window" => self.ignore_rules.push(
+                WindowRule::from_str(value)
+                    .map_err(|message| format!("{} for setting {}", message, identifier))?,
+            ),
+            "scratchpad_width" => self.scratchpad_width = parse_percentage(identifier, value)?,
+            "scratchpad_height" => self.scratchpad_height = parse_percentage(identifier, value)?,
+            "stack_tabs" => self.stack_tabs = parse_bool(identifier, value)?,
+            "stack_tab_height" => {
+                self.stack_tab_height = parse_number(identifier, value, "height")?
+            }
+            "sequence_timeout" => {
+                self.sequence_timeout = parse_number(identifier, value, "timeout")?
+            }
+            "watch_config" => self.watch_config = parse_bool(identifier, value)?,
+            "split_axis" | "taskbar_offset" | "workspace_count" => {
+                self.layout.apply(identifier, value)?
+            }
+            _ => return Err(format!("Unknown setting {}", identifier)),
+        }
+        return Ok(());
+    }
+
+    // Called once every entry is applied, to fill in defaults which depend on what was configured
+    pub fn finish(&mut self) {
+        if self.ignore_rules.is_empty() {
+            self.ignore_rules.push(WindowRule {
+                title: Some(String::from(DEFAULT_IGNORED_TITLE)),
+                ..WindowRule::default()
+            });
+        }
+    }
+
+    // Layout of a monitor, by its device name like DISPLAY2
+    pub fn monitor_layout(&self, monitor_name: &str) -> LayoutSettings {
+        let monitor_name = monitor_name.trim_start_matches(['\\', '.']);
+        return match self.monitors.iter().find(|(name, _)| {
+            name.trim_start_matches(['\\', '.'])
+                .eq_ignore_ascii_case(monitor_name)
+        }) {
+            Some((_, layout)) => layout.or(&self.layout),
+            None => self.layout.clone(),
+        };
+    }
+
+    // Name of the scratchpad a scratchpad_window rule matching the window title stores it under
+    pub fn scratchpad_rule(&self, title: &str) -> Option<String> {
+        let title = title.to_lowercase();
+        return self
+            .scratchpad_rules
+            .iter()
+            .find(|(_, rule)| title.contains(&rule.to_lowercase()))
+            .map(|(name, _)| name.clone());
+    }
+
+    pub fn sticky_rule(&self, title: &str) -> bool {
+        let title = title.to_lowercase();
+        return self
+            .sticky_windows
+            .iter()
+            .any(|rule| title.contains(&rule.to_lowercase()));
+    }
+
+    // Whether a rule keeps the window out of the tiled layout from the start
+    pub fn floats_by_rule(&self, title: &str) -> bool {
+        return self.scratchpad_rule(title).is_some()
+            || (self.sticky_rule(title) && !self.sticky_tiled);
+    }
+}
+
+// The section a setting belongs in, None when there is no such setting
+fn setting_section(identifier: &str) -> Option<Section> {
+    let identifier = identifier.to_ascii_lowercase();
+    return SETTINGS
+        .iter()
+        .find(|(name, _)| *name == identifier)
+        .map(|(_, section)| section.clone());
+}
+
+fn parse_percentage(identifier: &str, value: &str) -> Result<u32, String> {
+    let percentage = parse_number(identifier, value, "percentage")?;
+    if !(1..=100).contains(&percentage) {
+        return Err(format!(
+            "Setting {} must be a percentage between 1 and 100",
+            identifier
+        ));
+    }
+    return Ok(percentage);
+}
+
+fn parse_number(identifier: &str, value: &str, kind: &str) -> Result<u32, String> {
+    u32::from_str(value)
+        .map_err(|_| format!("Invalid {} '{}' for setting {}", kind, value, identifier))
+}
+
+fn parse_bool(identifier: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => Ok(true),
+        "false" | "no" | "off" => Ok(false),
+        _ => Err(format!(
+            "Invalid value '{}' for setting {}",
+            value, identifier
+        )),
+    }
+}
+
+// Colors are written as #RRGGBB (or 0xRRGGBB)
+fn parse_color(identifier: &str, value: &str) -> Result<u32, String> {
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    match u32::from_str_radix(hex, 16) {
+        Ok(color) if hex.len() == 6 => Ok(color),
+        _ => Err(format!(
+            "Invalid color '{}' for setting {}",
+            value, identifier
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::config::Settings;
+
+    #[test]
+    fn rules_float_scratchpad_and_untiled_sticky_windows() {
+        let mut settings = Settings {
+            scratchpad_rules: vec![(String::from("notes"), String::from("Notepad"))],
+            sticky_windows: vec![String::from("spotify")],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.scratchpad_rule("todo.txt - notepad"),
+            Some(String::from("notes"))
+        );
+        assert!(settings.floats_by_rule("todo.txt - Notepad"));
+        assert!(settings.floats_by_rule("Spotify Premium"));
+        assert!(!settings.floats_by_rule("Windows Terminal"));
+        settings.sticky_tiled = true;
+        assert!(settings.sticky_rule("Spotify Premium"));
+        assert!(!settings.floats_by_rule("Spotify Premium"));
+    }
+}
@@ -0,0 +1,17 @@
+use windows::Win32::Foundation::{HWND, POINT};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DragKind {
+    // Swap the window into the slot it's dropped on
+    MOVE,
+    // Move the window's edge along with the cursor
+    RESIZE,
+}
+
+// A window dragged by a mouse keybind, until its mouse button is released
+#[derive(Clone, Copy, Debug)]
+pub struct Drag {
+    pub kind: DragKind,
+    pub hwnd: HWND,
+    pub last_point: POINT,
+}
@@ -1,10 +1,59 @@
 use crate::data::common::axis::Axis;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
 use windows::Win32::Foundation::{HWND, RECT};
 
+#[derive(Copy, Clone, PartialEq)]
+pub enum Layout {
+    // Windows divide the group rect along the split axis
+    SPLIT,
+    // Windows share the group rect, only the top one is visible
+    STACKED,
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match &self {
+            Layout::SPLIT => String::from("split"),
+            Layout::STACKED => String::from("stacked"),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl Debug for Layout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
 #[derive(Debug)]
 pub struct Group {
     pub index: usize,
     pub split_axis: Axis,
+    pub layout: Layout,
     pub rect: RECT,
     pub windows: Vec<HWND>,
+    // Relative size of each window along the split axis, keyed by hwnd. Missing entries weigh 1
+    pub weights: HashMap<isize, f32>,
+}
+
+impl Group {
+    pub fn weight(&self, hwnd: &HWND) -> f32 {
+        *self.weights.get(&hwnd.0).unwrap_or(&1.0)
+    }
+}
+
+// Split a group rect into the tab strip along its top edge and the area left for the windows
+pub fn tab_strip(rect: &RECT, tab_height: i32) -> (RECT, RECT) {
+    let height = tab_height.clamp(0, rect.bottom - rect.top);
+    let strip = RECT {
+        bottom: rect.top + height,
+        ..*rect
+    };
+    let content = RECT {
+        top: rect.top + height,
+        ..*rect
+    };
+    (strip, content)
 }
@@ -1,11 +1,14 @@
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use windows::System::VirtualKey;
 
 use crate::data::action::WindowManagerAction;
-use crate::win_api::keyboard::{get_key_code, get_key_name};
+use crate::data::sequence::Trigger;
+use crate::data::window::WindowRule;
+use crate::win_api::keyboard::get_key_name;
 
 pub enum KeyEventType {
     PRESS,
@@ -28,12 +31,15 @@ impl Debug for KeyEventType {
 // action codes
 const KEY_UP: usize = 0x0101;
 const KEY_DOWN: usize = 0x0100;
+// Sent instead of KEY_DOWN / KEY_UP while ALT is held
+const SYS_KEY_DOWN: usize = 0x0104;
+const SYS_KEY_UP: usize = 0x0105;
 
 impl From<usize> for KeyEventType {
     fn from(code: usize) -> Self {
         match code {
-            KEY_UP => KeyEventType::RELEASE,
-            KEY_DOWN => KeyEventType::PRESS,
+            KEY_UP | SYS_KEY_UP => KeyEventType::RELEASE,
+            KEY_DOWN | SYS_KEY_DOWN => KeyEventType::PRESS,
             _ => KeyEventType::RELEASE,
         }
     }
@@ -71,37 +77,293 @@ impl Ord for Key {
 }
 
 pub const KEY_SPACE: i32 = VirtualKey::Space.0;
-pub const WINDOWS_KEY_CODE: i32 = VirtualKey::LeftWindows.0;
-pub const KEY_CONTROL: i32 = VirtualKey::LeftControl.0;
-pub const KEY_ALT: i32 = VirtualKey::LeftMenu.0;
-pub const KEY_SHIFT: i32 = VirtualKey::LeftShift.0;
+pub const KEY_ESCAPE: i32 = VirtualKey::Escape.0;
+pub const KEY_LEFT_WIN: i32 = VirtualKey::LeftWindows.0;
+pub const KEY_RIGHT_WIN: i32 = VirtualKey::RightWindows.0;
+// Generic modifiers match the key on either side of the keyboard
+pub const KEY_CONTROL: i32 = VirtualKey::Control.0;
+pub const KEY_ALT: i32 = VirtualKey::Menu.0;
+pub const KEY_SHIFT: i32 = VirtualKey::Shift.0;
+// There is no virtual key for either WIN key, so it gets a code outside the virtual key range
+pub const KEY_WIN: i32 = 0x100;
+// Mouse buttons keep their virtual key codes, the scroll wheel is placed next to WIN
+pub const KEY_LEFT_DRAG: i32 = VirtualKey::LeftButton.0;
+pub const KEY_RIGHT_DRAG: i32 = VirtualKey::RightButton.0;
+pub const KEY_MIDDLE_DRAG: i32 = VirtualKey::MiddleButton.0;
+pub const KEY_WHEEL_UP: i32 = 0x101;
+pub const KEY_WHEEL_DOWN: i32 = 0x102;
+/*
+   Keys bound by physical position (sc:0x23) or by the character they type on the active keyboard
+   layout (char:h) are flagged above the virtual key range, with the scan code or code point below
+*/
+const SCAN_CODE_FLAG: i32 = 0x10000;
+const CHARACTER_FLAG: i32 = 0x1000000;
+const SCAN_CODE_PREFIX: &str = "SC:";
+const CHARACTER_PREFIX: &str = "CHAR:";
+
+pub fn scan_code_key(scan_code: i32) -> i32 {
+    return SCAN_CODE_FLAG | scan_code;
+}
+
+// Characters are compared case-insensitively, shift is bound as its own key
+pub fn character_key(character: char) -> i32 {
+    let character = character.to_uppercase().next().unwrap_or(character);
+    return CHARACTER_FLAG | character as i32;
+}
+
+/*
+   Key names accepted in the config, mapped to their virtual key codes. The first name listed for
+   a code is the one it is displayed with
+*/
+const KEY_NAMES: &[(&str, i32)] = &[
+    ("WIN", KEY_WIN),
+    ("LWIN", KEY_LEFT_WIN),
+    ("RWIN", KEY_RIGHT_WIN),
+    ("CTRL", KEY_CONTROL),
+    ("CONTROL", KEY_CONTROL),
+    ("LCTRL", 0xA2),
+    ("RCTRL", 0xA3),
+    ("ALT", KEY_ALT),
+    ("LALT", 0xA4),
+    ("RALT", 0xA5),
+    ("ALTGR", 0xA5),
+    ("SHIFT", KEY_SHIFT),
+    ("LSHIFT", 0xA0),
+    ("RSHIFT", 0xA1),
+    ("SPACE", KEY_SPACE),
+    ("ESCAPE", KEY_ESCAPE),
+    ("ESC", KEY_ESCAPE),
+    ("ENTER", 0x0D),
+    ("RETURN", 0x0D),
+    ("TAB", 0x09),
+    ("BACKSPACE", 0x08),
+    ("CAPSLOCK", 0x14),
+    ("NUMLOCK", 0x90),
+    ("SCROLLLOCK", 0x91),
+    ("PRINTSCREEN", 0x2C),
+    ("PAUSE", 0x13),
+    ("MENU", 0x5D),
+    ("INSERT", 0x2D),
+    ("DELETE", 0x2E),
+    ("HOME", 0x24),
+    ("END", 0x23),
+    ("PAGEUP", 0x21),
+    ("PAGEDOWN", 0x22),
+    ("LEFT", 0x25),
+    ("UP", 0x26),
+    ("RIGHT", 0x27),
+    ("DOWN", 0x28),
+    ("SEMICOLON", 0xBA),
+    (";", 0xBA),
+    ("EQUALS", 0xBB),
+    ("=", 0xBB),
+    ("COMMA", 0xBC),
+    (",", 0xBC),
+    ("MINUS", 0xBD),
+    ("-", 0xBD),
+    ("PERIOD", 0xBE),
+    (".", 0xBE),
+    ("SLASH", 0xBF),
+    ("/", 0xBF),
+    ("GRAVE", 0xC0),
+    ("`", 0xC0),
+    ("LBRACKET", 0xDB),
+    ("[", 0xDB),
+    ("BACKSLASH", 0xDC),
+    ("\\", 0xDC),
+    ("RBRACKET", 0xDD),
+    ("]", 0xDD),
+    ("QUOTE", 0xDE),
+    ("'", 0xDE),
+    ("NUMPAD0", 0x60),
+    ("NUMPAD1", 0x61),
+    ("NUMPAD2", 0x62),
+    ("NUMPAD3", 0x63),
+    ("NUMPAD4", 0x64),
+    ("NUMPAD5", 0x65),
+    ("NUMPAD6", 0x66),
+    ("NUMPAD7", 0x67),
+    ("NUMPAD8", 0x68),
+    ("NUMPAD9", 0x69),
+    ("NUMPADMULTIPLY", 0x6A),
+    ("NUMPADADD", 0x6B),
+    ("NUMPADSUBTRACT", 0x6D),
+    ("NUMPADDECIMAL", 0x6E),
+    ("NUMPADDIVIDE", 0x6F),
+    ("VOLUMEMUTE", 0xAD),
+    ("VOLUMEDOWN", 0xAE),
+    ("VOLUMEUP", 0xAF),
+    ("MEDIANEXT", 0xB0),
+    ("MEDIAPREV", 0xB1),
+    ("MEDIASTOP", 0xB2),
+    ("MEDIAPLAY", 0xB3),
+    ("LEFTDRAG", KEY_LEFT_DRAG),
+    ("RIGHTDRAG", KEY_RIGHT_DRAG),
+    ("MIDDLEDRAG", KEY_MIDDLE_DRAG),
+    ("WHEELUP", KEY_WHEEL_UP),
+    ("WHEELDOWN", KEY_WHEEL_DOWN),
+];
+
+// Letters, digits and function keys follow each other in the virtual key range
+const KEY_A: i32 = 0x41;
+const KEY_0: i32 = 0x30;
+const KEY_F1: i32 = 0x70;
+const FUNCTION_KEYS: i32 = 24;
+
+pub fn key_code(name: &str) -> Option<i32> {
+    let name = name.trim();
+    if let Some(character) = strip_prefix(name, CHARACTER_PREFIX) {
+        let mut chars = character.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(character), None) => Some(character_key(character)),
+            _ => None,
+        };
+    }
+    if let Some(scan_code) = strip_prefix(name, SCAN_CODE_PREFIX) {
+        let scan_code = scan_code
+            .strip_prefix("0x")
+            .or_else(|| scan_code.strip_prefix("0X"))
+            .unwrap_or(scan_code);
+        return match i32::from_str_radix(scan_code, 16) {
+            Ok(scan_code) if (1..=0xFFFF).contains(&scan_code) => Some(scan_code_key(scan_code)),
+            _ => None,
+        };
+    }
+    let name = name.to_ascii_uppercase();
+    if let Some((_, code)) = KEY_NAMES.iter().find(|(key, _)| *key == name) {
+        return Some(*code);
+    }
+    let mut chars = name.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return match char {
+            'A'..='Z' => Some(KEY_A + (char as i32 - 'A' as i32)),
+            '0'..='9' => Some(KEY_0 + (char as i32 - '0' as i32)),
+            _ => None,
+        };
+    }
+    let function_key = name
+        .strip_prefix('F')
+        .and_then(|number| number.parse::<i32>().ok());
+    return match function_key {
+        Some(number) if (1..=FUNCTION_KEYS).contains(&number) => Some(KEY_F1 + number - 1),
+        _ => None,
+    };
+}
+
+fn strip_prefix<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    match name.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&name[prefix.len()..]),
+        _ => None,
+    }
+}
+
+pub fn key_name(code: i32) -> Option<String> {
+    if code & CHARACTER_FLAG != 0 {
+        let character = char::from_u32((code & !CHARACTER_FLAG) as u32)?;
+        return Some(format!("char:{}", character.to_lowercase()));
+    }
+    if code & SCAN_CODE_FLAG != 0 {
+        return Some(format!("sc:0x{:02X}", code & !SCAN_CODE_FLAG));
+    }
+    if let Some((name, _)) = KEY_NAMES.iter().find(|(_, key)| *key == code) {
+        return Some(name.to_string());
+    }
+    return match code {
+        KEY_A..=0x5A | KEY_0..=0x39 => char::from_u32(code as u32).map(String::from),
+        _ if (KEY_F1..KEY_F1 + FUNCTION_KEYS).contains(&code) => {
+            Some(format!("F{}", code - KEY_F1 + 1))
+        }
+        _ => None,
+    };
+}
+
+// The generic modifier matching a left or right hand modifier
+pub fn generic_key(code: i32) -> Option<i32> {
+    return match code {
+        KEY_LEFT_WIN | KEY_RIGHT_WIN => Some(KEY_WIN),
+        0xA2 | 0xA3 => Some(KEY_CONTROL),
+        0xA4 | 0xA5 => Some(KEY_ALT),
+        0xA0 | 0xA1 => Some(KEY_SHIFT),
+        _ => None,
+    };
+}
+
+// Every representation a key press can be matched on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PressedKey {
+    pub virtual_key: i32,
+    // Extended keys carry the 0xE0 prefix in the high byte
+    pub scan_code: i32,
+    // Character typed by the key on the active keyboard layout
+    pub character: Option<char>,
+}
+
+impl PressedKey {
+    pub fn new(virtual_key: i32, scan_code: i32, character: Option<char>) -> Self {
+        Self {
+            virtual_key,
+            scan_code,
+            character,
+        }
+    }
+}
+
+// True when a key bound as `bound` is satisfied by the key press, using the representation it was bound by
+pub fn key_matches(bound: i32, pressed: &PressedKey) -> bool {
+    if bound & CHARACTER_FLAG != 0 {
+        return pressed.character.map(character_key) == Some(bound);
+    }
+    if bound & SCAN_CODE_FLAG != 0 {
+        return scan_code_key(pressed.scan_code) == bound;
+    }
+    return bound == pressed.virtual_key || generic_key(pressed.virtual_key) == Some(bound);
+}
+
+pub fn is_modifier(code: i32) -> bool {
+    return [KEY_WIN, KEY_CONTROL, KEY_ALT, KEY_SHIFT].contains(&code)
+        || generic_key(code).is_some();
+}
+
+// Modifiers are written in the order WIN, CTRL, ALT, SHIFT, before any other key
+fn modifier_rank(code: i32) -> usize {
+    let generic = generic_key(code).unwrap_or(code);
+    return [KEY_WIN, KEY_CONTROL, KEY_ALT, KEY_SHIFT]
+        .iter()
+        .position(|modifier| *modifier == generic)
+        .unwrap_or(4);
+}
+
+pub fn is_mouse_button(code: i32) -> bool {
+    return [KEY_LEFT_DRAG, KEY_RIGHT_DRAG, KEY_MIDDLE_DRAG].contains(&code);
+}
+
+pub fn is_mouse_key(code: i32) -> bool {
+    return is_mouse_button(code) || code == KEY_WHEEL_UP || code == KEY_WHEEL_DOWN;
+}
 
 impl From<i32> for Key {
     fn from(code: i32) -> Self {
-        let name = match code {
-            KEY_SPACE => "SPACE".to_string(),
-            WINDOWS_KEY_CODE => "WIN".to_string(),
-            KEY_CONTROL => "CTRL".to_string(),
-            KEY_ALT => "ALT".to_string(),
-            KEY_SHIFT => "SHIFT".to_string(),
-            _ => String::from(get_key_name(code)),
-        };
+        let name = key_name(code).unwrap_or_else(|| get_key_name(code));
         Key { code, name }
     }
 }
 
 impl FromStr for Key {
-    type Err = ();
+    type Err = String;
 
     fn from_str(key: &str) -> Result<Self, Self::Err> {
-        let key_code: i32 = get_key_code(key);
-        Ok(Key::from(key_code))
+        match key_code(key) {
+            Some(code) => Ok(Key::from(code)),
+            None => Err(format!("Unknown key name '{}'", key.trim())),
+        }
     }
 }
 
 pub struct KeyEvent {
     pub event: KeyEventType,
     pub key: Key,
+    pub scan_code: i32,
+    pub character: Option<char>,
 }
 
 impl Debug for KeyEvent {
@@ -111,18 +373,92 @@ impl Debug for KeyEvent {
 }
 
 impl KeyEvent {
-    pub fn new(event: KeyEventType, key: Key) -> Self {
-        KeyEvent { event, key }
+    pub fn new(event: KeyEventType, key: Key, scan_code: i32, character: Option<char>) -> Self {
+        KeyEvent {
+            event,
+            key,
+            scan_code,
+            character,
+        }
+    }
+
+    pub fn pressed_key(&self) -> PressedKey {
+        PressedKey::new(self.key.code, self.scan_code, self.character)
     }
 }
 
 pub struct Keybind {
-    pub keys: Vec<Key>,
-    pub action: WindowManagerAction,
+    // Chords pressed one after another, a plain keybind is a sequence of one chord
+    pub sequence: Vec<Vec<Key>>,
+    // Executed in order, a keybind usually holds a single action
+    pub actions: Vec<WindowManagerAction>,
+    // Binding mode the keybind belongs to, global keybinds have no mode
+    pub mode: Option<String>,
+    pub trigger: Trigger,
+    // Application the keybind is limited to, overriding global keybinds of the same keys there
+    pub rule: Option<WindowRule>,
+    // Config file and line the keybind was defined on, line 0 for keybinds Fenestra adds itself
+    pub file: PathBuf,
+    pub line: usize,
+    // Defined inside a conditional block that applies, replacing earlier keybinds of the same keys
+    pub conditional: bool,
 }
 
 impl Keybind {
-    pub fn new(keys: Vec<Key>, action: WindowManagerAction) -> Self {
-        Keybind { keys, action }
+    pub fn new(
+        sequence: Vec<Vec<Key>>,
+        actions: Vec<WindowManagerAction>,
+        mode: Option<String>,
+    ) -> Self {
+        Keybind {
+            sequence,
+            actions,
+            mode,
+            trigger: Trigger::default(),
+            rule: None,
+            file: PathBuf::new(),
+            line: 0,
+            conditional: false,
+        }
+    }
+
+    // Where the keybind was defined, ex. 'fenestra.conf:12'
+    pub fn source(&self) -> String {
+        if self.line == 0 {
+            return String::from("(added by Fenestra)");
+        }
+        return format!("{}:{}", self.file.display(), self.line);
+    }
+
+    // The options as written in square brackets after the keys, ex. 'repeat, exe=firefox.exe'
+    pub fn options(&self) -> String {
+        let mut options: Vec<String> = Vec::new();
+        if self.trigger.on_release {
+            options.push(String::from("on_release"));
+        }
+        if self.trigger.repeat {
+            options.push(String::from("repeat"));
+        }
+        if let Some(rule) = &self.rule {
+            options.push(rule.to_string());
+        }
+        return options.join(", ");
+    }
+
+    // The keys as written in the config, modifiers first, ex. 'WIN + SPACE, W'
+    pub fn sequence_string(&self) -> String {
+        return self
+            .sequence
+            .iter()
+            .map(|chord| {
+                let mut keys: Vec<&Key> = chord.iter().collect();
+                keys.sort_by_key(|key| modifier_rank(key.code));
+                keys.iter()
+                    .map(|key| key.name.clone())
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
     }
 }
@@ -1,8 +1,12 @@
 pub mod action;
+pub mod border;
 pub mod common;
+pub mod config;
+pub mod drag;
 pub mod group;
 pub mod key;
 pub mod monitor;
+pub mod sequence;
 pub mod window;
 pub mod workspace;
 pub mod hook;
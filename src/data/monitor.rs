@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 
+use windows::Win32::Foundation::RECT;
 use windows::Win32::Graphics::Gdi::{DEVMODEA, HMONITOR, MONITORINFO};
 use windows::Win32::UI::Shell::Common::DEVICE_SCALE_FACTOR;
 
+use crate::data::common::axis::Axis;
 use crate::data::common::direction::Direction;
+use crate::data::config::LayoutSettings;
 
 #[derive(Clone)]
 pub struct Monitor {
@@ -17,6 +20,33 @@ pub struct Monitor {
     pub workspaces: Vec<usize>,
 }
 
+impl Monitor {
+    pub fn is_landscape(&self) -> bool {
+        self.device_mode.dmPelsWidth > self.device_mode.dmPelsHeight
+    }
+
+    // The area windows are tiled in and the axis they are split along, as set by the layout
+    pub fn layout_area(&self, layout: &LayoutSettings) -> (RECT, Axis) {
+        let position = unsafe { self.device_mode.Anonymous1.Anonymous2.dmPosition };
+        let taskbar_offset = match layout.taskbar_offset {
+            Some(taskbar_offset) => taskbar_offset as i32,
+            None => self.info.rcMonitor.bottom - self.info.rcWork.bottom,
+        };
+        let area = RECT {
+            left: position.x,
+            top: position.y,
+            right: position.x + self.device_mode.dmPelsWidth as i32,
+            bottom: position.y + self.device_mode.dmPelsHeight as i32 - taskbar_offset,
+        };
+        let split_axis = match (layout.split_axis, self.is_landscape()) {
+            (Some(split_axis), _) => split_axis,
+            (None, true) => Axis::VERTICAL,
+            (None, false) => Axis::HORIZONTAL,
+        };
+        return (area, split_axis);
+    }
+}
+
 impl PartialEq for Monitor {
     fn eq(&self, other: &Self) -> bool {
         self.hmonitor == other.hmonitor
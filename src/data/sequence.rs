@@ -0,0 +1,412 @@
+use std::time::{Duration, Instant};
+
+use crate::data::key::KeyEventType::{PRESS, RELEASE};
+use crate::data::key::{is_modifier, key_matches, KeyEvent, PressedKey};
+
+// A chord is the sorted set of key codes held down together
+pub type Chord = Vec<i32>;
+
+// True when the keys pressed satisfy a bound chord, generic modifiers matching either side
+pub fn chord_matches(bound: &Chord, pressed: &[PressedKey]) -> bool {
+    bound.len() == pressed.len()
+        && bound.iter().all(|key| {
+            pressed
+                .iter()
+                .any(|pressed_key| key_matches(*key, pressed_key))
+        })
+}
+
+struct Node {
+    chord: Chord,
+    children: Vec<usize>,
+    bindings: Vec<usize>,
+}
+
+// Prefix trie of key sequences, each path of chords from the root leads to the bindings it triggers
+pub struct SequenceTrie {
+    nodes: Vec<Node>,
+}
+
+pub const ROOT: usize = 0;
+
+impl SequenceTrie {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                chord: Vec::new(),
+                children: Vec::new(),
+                bindings: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn insert(&mut self, sequence: &[Chord], binding: usize) {
+        let mut node = ROOT;
+        for chord in sequence {
+            node = match self.step(node, chord) {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node {
+                        chord: chord.clone(),
+                        children: Vec::new(),
+                        bindings: Vec::new(),
+                    });
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.push(child);
+                    child
+                }
+            };
+        }
+        self.nodes[node].bindings.push(binding);
+    }
+
+    pub fn step(&self, node: usize, chord: &Chord) -> Option<usize> {
+        self.nodes[node]
+            .children
+            .iter()
+            .find(|child| self.nodes[**child].chord == *chord)
+            .cloned()
+    }
+
+    /*
+       Find the child matching the keys pressed, preferring the chord bound by exactly these
+       virtual keys over generic modifiers, scan codes and characters
+    */
+    pub fn matching_step(&self, node: usize, chord: &[PressedKey]) -> Option<usize> {
+        let virtual_keys: Chord = chord.iter().map(|key| key.virtual_key).collect();
+        self.step(node, &virtual_keys).or_else(|| {
+            self.nodes[node]
+                .children
+                .iter()
+                .find(|child| chord_matches(&self.nodes[**child].chord, chord))
+                .cloned()
+        })
+    }
+
+    pub fn bindings(&self, node: usize) -> &[usize] {
+        &self.nodes[node].bindings
+    }
+
+    pub fn has_children(&self, node: usize) -> bool {
+        !self.nodes[node].children.is_empty()
+    }
+
+    // Chords leading on from the node, skipping the ones whose bindings don't count as bound
+    pub fn chords(&self, node: usize, bound: &impl Fn(&[usize]) -> bool) -> Vec<Chord> {
+        self.nodes[node]
+            .children
+            .iter()
+            .filter(|child| self.has_children(**child) || bound(self.bindings(**child)))
+            .map(|child| self.nodes[*child].chord.clone())
+            .collect()
+    }
+}
+
+// How a binding reacts to the way its final chord is pressed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Trigger {
+    // Run when a key of the final chord is released instead of when it is pressed
+    pub on_release: bool,
+    // Run again for every auto-repeated key press while the final chord is held
+    pub repeat: bool,
+}
+
+/*
+   Feeds key events through one trie per binding mode. A chord is complete when a key is pressed,
+   at which point the pending sequence either advances, fires, or is flushed. Sequences which are
+   also the prefix of a longer sequence fire once the timeout passes
+*/
+pub struct SequenceMatcher {
+    tries: Vec<(Option<String>, SequenceTrie)>,
+    triggers: Vec<Trigger>,
+    timeout: Duration,
+    pressed_keys: Vec<PressedKey>,
+    mode: Option<String>,
+    node: usize,
+    deadline: Option<Instant>,
+    // Node whose bindings fired on the last press, repeated while its chord is held
+    held: Option<usize>,
+    // Node whose on_release bindings wait for the chord to be released
+    release: Option<usize>,
+}
+
+impl SequenceMatcher {
+    pub fn new(sequences: Vec<(Option<String>, Vec<Chord>, Trigger)>, timeout: Duration) -> Self {
+        let mut tries: Vec<(Option<String>, SequenceTrie)> = Vec::new();
+        let mut triggers: Vec<Trigger> = Vec::new();
+        for (binding, (mode, sequence, trigger)) in sequences.into_iter().enumerate() {
+            let index = match tries.iter().position(|(m, _)| *m == mode) {
+                Some(index) => index,
+                None => {
+                    tries.push((mode, SequenceTrie::new()));
+                    tries.len() - 1
+                }
+            };
+            tries[index].1.insert(&sequence, binding);
+            triggers.push(trigger);
+        }
+        Self {
+            tries,
+            triggers,
+            timeout,
+            pressed_keys: Vec::new(),
+            mode: None,
+            node: ROOT,
+            deadline: None,
+            held: None,
+            release: None,
+        }
+    }
+
+    // True while a partial sequence is being held
+    pub fn is_pending(&self) -> bool {
+        self.node != ROOT
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /*
+       Chords which would currently advance or start a sequence in the given binding mode. Chords
+       completing a sequence only count when the bound function accepts the bindings they'd run
+    */
+    pub fn bound_chords(&self, mode: Option<&str>, bound: impl Fn(&[usize]) -> bool) -> Vec<Chord> {
+        let trie = match self.tries.iter().find(|(m, _)| m.as_deref() == mode) {
+            Some((_, trie)) => trie,
+            None => return Vec::new(),
+        };
+        let mut chords = trie.chords(ROOT, &bound);
+        if self.mode.as_deref() == mode && self.node != ROOT {
+            chords.extend(trie.chords(self.node, &bound));
+        }
+        return chords;
+    }
+
+    // Returns the bindings to execute after the given key event
+    pub fn handle(&mut self, key_event: &KeyEvent, mode: Option<&str>, now: Instant) -> Vec<usize> {
+        let mut fired = self.expire(now);
+        if self.mode.as_deref() != mode {
+            // Partial sequences don't carry over between binding modes
+            self.mode = mode.map(String::from);
+            self.node = ROOT;
+            self.deadline = None;
+            self.held = None;
+            self.release = None;
+        }
+        let pressed_key = key_event.pressed_key();
+        let code = pressed_key.virtual_key;
+        match key_event.event {
+            PRESS => {
+                if self.pressed_keys.iter().any(|key| key.virtual_key == code) {
+                    // Auto-repeat only re-runs the bindings which opted into it
+                    if let Some(node) = self.held {
+                        fired.extend(
+                            self.select(node, |trigger| trigger.repeat && !trigger.on_release),
+                        );
+                    }
+                    return fired;
+                }
+                self.pressed_keys.push(pressed_key);
+                self.held = None;
+                let mut chord = self.pressed_keys.clone();
+                chord.sort_by_key(|key| key.virtual_key);
+                fired.extend(self.advance(&chord, now));
+            }
+            RELEASE => {
+                if let Some(node) = self.release.take() {
+                    fired.extend(self.select(node, |trigger| trigger.on_release));
+                }
+                self.held = None;
+                self.pressed_keys.retain(|key| key.virtual_key != code);
+            }
+        }
+        return fired;
+    }
+
+    // Flush a partial sequence once its timeout has passed, firing it if it is complete on its own
+    pub fn expire(&mut self, now: Instant) -> Vec<usize> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => self.flush(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn advance(&mut self, chord: &[PressedKey], now: Instant) -> Vec<usize> {
+        let pending = self.node;
+        let (mut fired, next, has_children) = match self.trie() {
+            Some(trie) => {
+                let mut fired = Vec::new();
+                let mut next = trie.matching_step(pending, chord);
+                if next.is_none() && chord.iter().all(|key| is_modifier(key.virtual_key)) {
+                    // Modifiers are still being pressed on the way to the next chord
+                    return fired;
+                }
+                if next.is_none() && pending != ROOT {
+                    // The chord doesn't extend the pending sequence, flush it and start over
+                    fired.extend_from_slice(trie.bindings(pending));
+                    next = trie.matching_step(ROOT, chord);
+                }
+                let has_children = next.is_some_and(|node| trie.has_children(node));
+                (fired, next, has_children)
+            }
+            None => return Vec::new(),
+        };
+        self.node = ROOT;
+        self.deadline = None;
+        match next {
+            Some(node) if has_children => {
+                self.node = node;
+                self.deadline = Some(now + self.timeout);
+            }
+            Some(node) => {
+                self.held = Some(node);
+                self.release = Some(node);
+                fired.extend(self.select(node, |trigger| !trigger.on_release));
+            }
+            None => {}
+        }
+        return fired;
+    }
+
+    fn flush(&mut self) -> Vec<usize> {
+        let fired = match self.trie() {
+            Some(trie) => trie.bindings(self.node).to_vec(),
+            None => Vec::new(),
+        };
+        self.node = ROOT;
+        self.deadline = None;
+        return fired;
+    }
+
+    fn select(&self, node: usize, filter: fn(&Trigger) -> bool) -> Vec<usize> {
+        match self.trie() {
+            Some(trie) => trie
+                .bindings(node)
+                .iter()
+                .filter(|binding| filter(&self.triggers[**binding]))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn trie(&self) -> Option<&SequenceTrie> {
+        self.tries
+            .iter()
+            .find(|(mode, _)| *mode == self.mode)
+            .map(|(_, trie)| trie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::data::key::KeyEventType::{PRESS, RELEASE};
+    use crate::data::key::{Key, KeyEvent, KeyEventType, KEY_LEFT_WIN, KEY_WIN};
+    use crate::data::sequence::{Chord, SequenceMatcher, Trigger};
+
+    const KEY_A: i32 = 0x41;
+    const KEY_B: i32 = 0x42;
+    const KEY_C: i32 = 0x43;
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    fn matcher(sequences: Vec<(Option<&str>, Vec<Chord>)>) -> SequenceMatcher {
+        let sequences = sequences
+            .into_iter()
+            .map(|(mode, sequence)| (mode.map(String::from), sequence, Trigger::default()))
+            .collect();
+        return SequenceMatcher::new(sequences, TIMEOUT);
+    }
+
+    fn event(event: KeyEventType, code: i32) -> KeyEvent {
+        return KeyEvent::new(event, Key::from(code), 0, None);
+    }
+
+    // Press the keys in order and release them in reverse, collecting what fired
+    fn tap(
+        matcher: &mut SequenceMatcher,
+        codes: &[i32],
+        mode: Option<&str>,
+        now: Instant,
+    ) -> Vec<usize> {
+        let mut fired = Vec::new();
+        for code in codes {
+            fired.extend(matcher.handle(&event(PRESS, *code), mode, now));
+        }
+        for code in codes.iter().rev() {
+            fired.extend(matcher.handle(&event(RELEASE, *code), mode, now));
+        }
+        return fired;
+    }
+
+    #[test]
+    fn a_chord_fires_once_complete() {
+        let mut matcher = matcher(vec![(None, vec![vec![KEY_A, KEY_WIN]])]);
+        let now = Instant::now();
+        assert!(matcher
+            .handle(&event(PRESS, KEY_LEFT_WIN), None, now)
+            .is_empty());
+        assert_eq!(matcher.handle(&event(PRESS, KEY_A), None, now), vec![0]);
+    }
+
+    #[test]
+    fn a_prefix_waits_for_the_rest_of_the_sequence() {
+        let mut matcher = matcher(vec![
+            (None, vec![vec![KEY_A, KEY_WIN]]),
+            (None, vec![vec![KEY_A, KEY_WIN], vec![KEY_B]]),
+        ]);
+        let now = Instant::now();
+        assert!(tap(&mut matcher, &[KEY_LEFT_WIN, KEY_A], None, now).is_empty());
+        assert!(matcher.is_pending());
+        assert_eq!(tap(&mut matcher, &[KEY_B], None, now), vec![1]);
+        assert!(!matcher.is_pending());
+    }
+
+    #[test]
+    fn a_prefix_fires_on_its_own_after_the_timeout() {
+        let mut matcher = matcher(vec![
+            (None, vec![vec![KEY_A, KEY_WIN]]),
+            (None, vec![vec![KEY_A, KEY_WIN], vec![KEY_B]]),
+        ]);
+        let now = Instant::now();
+        tap(&mut matcher, &[KEY_LEFT_WIN, KEY_A], None, now);
+        assert!(matcher.expire(now + TIMEOUT / 2).is_empty());
+        assert_eq!(matcher.expire(now + TIMEOUT), vec![0]);
+        assert!(!matcher.is_pending());
+        assert!(tap(&mut matcher, &[KEY_B], None, now + TIMEOUT).is_empty());
+    }
+
+    #[test]
+    fn a_mismatch_resets_the_sequence() {
+        let mut matcher = matcher(vec![
+            (None, vec![vec![KEY_A, KEY_WIN], vec![KEY_B]]),
+            (None, vec![vec![KEY_C]]),
+        ]);
+        let now = Instant::now();
+        tap(&mut matcher, &[KEY_LEFT_WIN, KEY_A], None, now);
+        // The unrelated chord starts over from the root, where it is bound itself
+        assert_eq!(tap(&mut matcher, &[KEY_C], None, now), vec![1]);
+        assert!(!matcher.is_pending());
+        assert!(tap(&mut matcher, &[KEY_B], None, now).is_empty());
+    }
+
+    #[test]
+    fn sequences_only_match_in_their_binding_mode() {
+        let mut matcher = matcher(vec![
+            (None, vec![vec![KEY_A]]),
+            (Some("resize"), vec![vec![KEY_A]]),
+            (Some("resize"), vec![vec![KEY_B], vec![KEY_C]]),
+        ]);
+        let now = Instant::now();
+        assert_eq!(tap(&mut matcher, &[KEY_A], None, now), vec![0]);
+        assert_eq!(tap(&mut matcher, &[KEY_A], Some("resize"), now), vec![1]);
+        assert!(tap(&mut matcher, &[KEY_B], None, now).is_empty());
+        // Partial sequences don't carry over into another mode
+        tap(&mut matcher, &[KEY_B], Some("resize"), now);
+        assert!(matcher.is_pending());
+        assert!(tap(&mut matcher, &[KEY_C], None, now).is_empty());
+        assert!(!matcher.is_pending());
+    }
+}
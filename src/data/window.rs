@@ -1,6 +1,11 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 use windows::Win32::Foundation::{HWND, RECT};
 use windows::Win32::UI::WindowsAndMessaging::{WINDOWINFO, WINDOWPLACEMENT};
 
+use crate::config::lexer::{split_unquoted, unquote};
+
 #[derive(Debug, Clone)]
 pub struct Window {
     pub title: String,
@@ -15,6 +20,19 @@ pub struct Window {
     pub dpi: u32,
     pub style: i32,
     pub extended_style: i32,
+    pub class_name: String,
+    // File name of the executable owning the window, ex. 'vmconnect.exe'
+    pub executable: String,
+}
+
+impl Window {
+    pub fn metadata(&self) -> WindowMetadata {
+        WindowMetadata {
+            executable: self.executable.clone(),
+            class_name: self.class_name.clone(),
+            title: self.title.clone(),
+        }
+    }
 }
 
 impl Eq for Window {}
@@ -24,3 +42,156 @@ impl PartialEq for Window {
         self.hwnd == other.hwnd || self.title == other.title
     }
 }
+
+// What keybind rules get to see of the foreground window
+#[derive(Debug, Clone, Default)]
+pub struct WindowMetadata {
+    pub executable: String,
+    pub class_name: String,
+    pub title: String,
+}
+
+/*
+   Limits a keybind to windows of one application. Every part that is set has to match, executables
+   and classes by their full name, titles by a part of them, all ignoring case
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowRule {
+    pub executable: Option<String>,
+    pub class_name: Option<String>,
+    pub title: Option<String>,
+}
+
+impl WindowRule {
+    pub fn matches(&self, metadata: &WindowMetadata) -> bool {
+        let executable = self
+            .executable
+            .as_ref()
+            .is_none_or(|executable| executable.eq_ignore_ascii_case(&metadata.executable));
+        let class_name = self
+            .class_name
+            .as_ref()
+            .is_none_or(|class_name| class_name.eq_ignore_ascii_case(&metadata.class_name));
+        let title = self.title.as_ref().is_none_or(|title| {
+            metadata
+                .title
+                .to_lowercase()
+                .contains(&title.to_lowercase())
+        });
+        return executable && class_name && title;
+    }
+
+    // Set one part of the rule, returns false when there is no such part
+    pub fn set(&mut self, name: &str, value: &str) -> bool {
+        let value = Some(value.to_string());
+        match name.trim().to_ascii_lowercase().as_str() {
+            "exe" => self.executable = value,
+            "class" => self.class_name = value,
+            "title" => self.title = value,
+            _ => return false,
+        }
+        return true;
+    }
+}
+
+impl FromStr for WindowRule {
+    type Err = String;
+
+    // Written as 'exe=<name>, class=<name>, title=<part of the title>', leaving out any of them
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut rule = WindowRule::default();
+        for part in split_unquoted(input, ',', 1)
+            .into_iter()
+            .filter(|part| !part.text.is_empty())
+        {
+            let is_set = match part.text.split_once('=') {
+                Some((name, value)) => rule.set(name, unquote(value)),
+                None => false,
+            };
+            if !is_set {
+                return Err(format!(
+                    "Expected exe=, class= or title= instead of '{}'",
+                    part.text
+                ));
+            }
+        }
+        if rule == WindowRule::default() {
+            return Err(String::from(
+                "Expected at least one of exe=, class= or title=",
+            ));
+        }
+        return Ok(rule);
+    }
+}
+
+// Written back the way FromStr reads it, ex. 'exe=firefox.exe, title="Picture-in-Picture"'
+impl Display for WindowRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            ("exe", &self.executable),
+            ("class", &self.class_name),
+            ("title", &self.title),
+        ]
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .as_ref()
+                .map(|value| match value.contains([',', ' ', ']']) {
+                    true => format!("{}=\"{}\"", name, value),
+                    false => format!("{}={}", name, value),
+                })
+        })
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::window::{WindowMetadata, WindowRule};
+
+    fn firefox() -> WindowMetadata {
+        return WindowMetadata {
+            executable: String::from("firefox.exe"),
+            class_name: String::from("MozillaWindowClass"),
+            title: String::from("Picture-in-Picture"),
+        };
+    }
+
+    fn rule(text: &str) -> WindowRule {
+        return text.parse().unwrap();
+    }
+
+    #[test]
+    fn rules_match_every_part_they_set() {
+        assert!(rule("exe=firefox.exe").matches(&firefox()));
+        assert!(rule("class=MozillaWindowClass").matches(&firefox()));
+        assert!(rule("exe=firefox.exe, title=Picture").matches(&firefox()));
+        assert!(!rule("exe=firefox.exe, class=Chrome_WidgetWin_1").matches(&firefox()));
+        assert!(!rule("exe=chrome.exe").matches(&firefox()));
+    }
+
+    #[test]
+    fn rules_ignore_case() {
+        assert!(rule("exe=FireFox.EXE").matches(&firefox()));
+        assert!(rule("class=mozillawindowclass").matches(&firefox()));
+        assert!(rule("title=picture-IN").matches(&firefox()));
+    }
+
+    #[test]
+    fn titles_match_any_part_and_executables_the_whole_name() {
+        assert!(rule("title=in-Pic").matches(&firefox()));
+        assert!(!rule("title=Picture in Picture").matches(&firefox()));
+        assert!(!rule("exe=firefox").matches(&firefox()));
+    }
+
+    #[test]
+    fn rules_need_a_known_part() {
+        assert!("".parse::<WindowRule>().is_err());
+        assert!("name=firefox.exe".parse::<WindowRule>().is_err());
+        assert_eq!(
+            rule("exe=firefox.exe, title=\"Picture, in\"").to_string(),
+            "exe=firefox.exe, title=\"Picture, in\""
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use windows::Win32::Foundation::{HWND, RECT};
+
+use crate::data::config::MAX_WORKSPACES;
+
 pub struct Workspace {
     pub index: usize,
     pub groups: Vec<usize>,
@@ -9,3 +13,88 @@ impl PartialEq for Workspace {
         self.index == other.index
     }
 }
+
+pub struct ScratchpadWindow {
+    pub hwnd: HWND,
+    pub name: Option<String>,
+    pub visible: bool,
+}
+
+// Workspaces are numbered like the number keys, so workspace 0 is the last one
+pub fn workspace_index(id: usize) -> usize {
+    return match id {
+        0 => MAX_WORKSPACES - 1,
+        id => id - 1,
+    };
+}
+
+// Center a rect of the given size on the area, cut off at the edges of the area
+pub fn centered_size(area: &RECT, width: i32, height: i32) -> RECT {
+    let area_width = area.right - area.left;
+    let area_height = area.bottom - area.top;
+    let width = width.min(area_width);
+    let height = height.min(area_height);
+    let left = area.left + (area_width - width) / 2;
+    let top = area.top + (area_height - height) / 2;
+    RECT {
+        left,
+        top,
+        right: left + width,
+        bottom: top + height,
+    }
+}
+
+// Size a rect as a percentage of the area and center it there
+pub fn centered_rect(area: &RECT, width_percent: u32, height_percent: u32) -> RECT {
+    let area_width = area.right - area.left;
+    let area_height = area.bottom - area.top;
+    let width = area_width * width_percent.min(100) as i32 / 100;
+    let height = area_height * height_percent.min(100) as i32 / 100;
+    let left = area.left + (area_width - width) / 2;
+    let top = area.top + (area_height - height) / 2;
+    RECT {
+        left,
+        top,
+        right: left + width,
+        bottom: top + height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::RECT;
+
+    use crate::data::workspace::{centered_size, workspace_index};
+
+    const AREA: RECT = RECT {
+        left: 1920,
+        top: 0,
+        right: 3840,
+        bottom: 1040,
+    };
+
+    #[test]
+    fn centers_a_smaller_rect() {
+        let rect = centered_size(&AREA, 400, 200);
+        assert_eq!(
+            (rect.left, rect.top, rect.right, rect.bottom),
+            (2680, 420, 3080, 620)
+        );
+    }
+
+    #[test]
+    fn cuts_a_larger_rect_off_at_the_area() {
+        let rect = centered_size(&AREA, 2500, 2000);
+        assert_eq!(
+            (rect.left, rect.top, rect.right, rect.bottom),
+            (1920, 0, 3840, 1040)
+        );
+    }
+
+    #[test]
+    fn workspace_0_is_the_last_one() {
+        assert_eq!(workspace_index(1), 0);
+        assert_eq!(workspace_index(9), 8);
+        assert_eq!(workspace_index(0), 9);
+    }
+}
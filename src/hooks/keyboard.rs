@@ -1,5 +1,7 @@
-use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
-use windows::Win32::UI::WindowsAndMessaging::{HC_ACTION, WM_KEYUP};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use windows::Win32::UI::WindowsAndMessaging::{HC_ACTION, LLKHF_EXTENDED, WM_KEYUP, WM_SYSKEYUP};
 use windows::Win32::{
     Foundation::{LPARAM, LRESULT, WPARAM},
     UI::WindowsAndMessaging::{HHOOK, KBDLLHOOKSTRUCT, WH_KEYBOARD_LL},
@@ -7,13 +9,57 @@ use windows::Win32::{
 
 use crate::data::common::event::Event;
 use crate::data::hook::Hook;
-use crate::data::key::WINDOWS_KEY_CODE;
+use crate::data::key::{generic_key, PressedKey, KEY_WIN};
+use crate::data::sequence::chord_matches;
+use crate::win_api::keyboard::MASK_KEY_CODE;
 use crate::win_api::window::send_event_message;
 use crate::{
     win_api,
     win_api::hook::{call_next_hook, set_window_hook},
 };
 
+// Set while a binding mode is active, every key pressed in a mode is swallowed
+static MODE_ACTIVE: AtomicBool = AtomicBool::new(false);
+// Keys whose press was swallowed, so their release is swallowed as well
+static SWALLOWED_KEYS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+// Chords which complete or continue a keybind, published by the KeyManager
+static BOUND_CHORDS: Mutex<Vec<Vec<i32>>> = Mutex::new(Vec::new());
+static PRESSED_KEYS: Mutex<Vec<PressedKey>> = Mutex::new(Vec::new());
+// Extended keys (arrows, right hand modifiers, ...) are told apart by the 0xE0 scan code prefix
+const EXTENDED_SCAN_CODE: u32 = 0xE000;
+// Set once a keybind using WIN was swallowed, so releasing WIN doesn't open the start menu
+static MASK_WIN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_mode_active(active: bool) {
+    MODE_ACTIVE.store(active, Ordering::SeqCst);
+}
+
+pub fn set_bound_chords(chords: Vec<Vec<i32>>) {
+    *BOUND_CHORDS.lock().unwrap() = chords;
+}
+
+/*
+   Called by the mouse hook for mouse buttons and the scroll wheel, true when they complete a
+   keybind together with the keys held down
+*/
+pub fn is_bound_mouse_key(code: i32) -> bool {
+    let mut chord: Vec<PressedKey> = PRESSED_KEYS.lock().unwrap().clone();
+    chord.push(PressedKey::new(code, 0, None));
+    let bound = BOUND_CHORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|bound_chord| chord_matches(bound_chord, &chord));
+    if bound
+        && chord
+            .iter()
+            .any(|key| generic_key(key.virtual_key) == Some(KEY_WIN))
+    {
+        MASK_WIN.store(true, Ordering::SeqCst);
+    }
+    return bound;
+}
+
 pub struct KeyboardHook {
     hook: HHOOK,
 }
@@ -31,17 +77,72 @@ pub unsafe extern "system" fn callback(code: i32, w_param: WPARAM, l_param: LPAR
     if code < 0 || code != HC_ACTION as i32 {
         return call_next_hook(code, w_param, l_param);
     }
-    // Post the key code back to the main application message queue
     let hook_struct: *mut KBDLLHOOKSTRUCT = l_param.0 as *mut KBDLLHOOKSTRUCT;
     let keyboard_hook_struct = hook_struct.as_ref().unwrap();
     let key_code = keyboard_hook_struct.vkCode;
-    send_event_message(Event::key_event(key_code as isize, w_param));
-    // Suppress the Windows key
-    let win_pressed = (GetAsyncKeyState(WINDOWS_KEY_CODE) & (1 << 15)) == (1 << 15);
-    if win_pressed {
-        if key_code != WINDOWS_KEY_CODE as u32 && w_param.0 != WM_KEYUP as usize {
-            return LRESULT(1);
+    if key_code == MASK_KEY_CODE {
+        return call_next_hook(code, w_param, l_param);
+    }
+    let mut scan_code = keyboard_hook_struct.scanCode;
+    if keyboard_hook_struct.flags.0 & LLKHF_EXTENDED.0 != 0 {
+        scan_code |= EXTENDED_SCAN_CODE;
+    }
+    let character = win_api::keyboard::key_character(key_code as i32);
+    // Post the key code back to the main application message queue
+    send_event_message(Event::key_event(
+        key_code as isize,
+        scan_code as isize,
+        character,
+        w_param,
+    ));
+    let key_released = w_param.0 == WM_KEYUP as usize || w_param.0 == WM_SYSKEYUP as usize;
+    let pressed_key = PressedKey::new(key_code as i32, scan_code as i32, character);
+    let mut pressed_keys = PRESSED_KEYS.lock().unwrap();
+    let mut chord: Vec<PressedKey> = pressed_keys.clone();
+    if key_released {
+        pressed_keys.retain(|key| key.virtual_key != pressed_key.virtual_key);
+    } else if !pressed_keys
+        .iter()
+        .any(|key| key.virtual_key == pressed_key.virtual_key)
+    {
+        pressed_keys.push(pressed_key);
+        chord.push(pressed_key);
+    }
+    drop(pressed_keys);
+    /*
+       Releases are only swallowed for keys whose press was swallowed, so keys held while
+       entering or leaving a binding mode are not left stuck
+    */
+    let mut swallowed_keys = SWALLOWED_KEYS.lock().unwrap();
+    if key_released && swallowed_keys.contains(&key_code) {
+        swallowed_keys.retain(|key| *key != key_code);
+        return LRESULT(1);
+    }
+    /*
+       Inside a binding mode every key press is swallowed, otherwise only the keys which
+       complete a keybind are, so shortcuts without a keybind keep reaching Windows
+    */
+    let bound = BOUND_CHORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|bound_chord| chord_matches(bound_chord, &chord));
+    if !key_released && (MODE_ACTIVE.load(Ordering::SeqCst) || bound) {
+        if !swallowed_keys.contains(&key_code) {
+            swallowed_keys.push(key_code);
         }
+        if chord
+            .iter()
+            .any(|key| generic_key(key.virtual_key) == Some(KEY_WIN))
+        {
+            MASK_WIN.store(true, Ordering::SeqCst);
+        }
+        return LRESULT(1);
+    }
+    drop(swallowed_keys);
+    let win_released = key_released && generic_key(key_code as i32) == Some(KEY_WIN);
+    if win_released && MASK_WIN.swap(false, Ordering::SeqCst) {
+        win_api::keyboard::send_mask_key();
     }
     return call_next_hook(code, w_param, l_param);
 }
@@ -1,13 +1,19 @@
 use log::debug;
+use crate::data::config::Settings;
 use crate::data::hook::Hook;
 use crate::hooks::keyboard::KeyboardHook;
+use crate::hooks::mouse::MouseHook;
 use crate::hooks::window::EventHook;
 
 pub mod keyboard;
+pub mod mouse;
 pub mod window;
 
-pub fn set_hooks() -> Vec<Box<dyn Hook>> {
+pub fn set_hooks(settings: &Settings) -> Vec<Box<dyn Hook>> {
     let mut hooks: Vec<Box<dyn Hook>> = vec![Box::new(KeyboardHook::new()), Box::new(EventHook::new())];
+    if settings.focus_follows_mouse || settings.mouse_bindings {
+        hooks.push(Box::new(MouseHook::new()));
+    }
     hooks.iter_mut().for_each(|hook| hook.set());
     debug!("Set hooks");
     return hooks;
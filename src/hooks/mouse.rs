@@ -0,0 +1,117 @@
+use std::sync::Mutex;
+
+use windows::Win32::UI::WindowsAndMessaging::{
+    HC_ACTION, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP,
+    WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP,
+};
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{HHOOK, MSLLHOOKSTRUCT, WH_MOUSE_LL},
+};
+
+use crate::data::common::event::Event;
+use crate::data::hook::Hook;
+use crate::data::key::{
+    KEY_LEFT_DRAG, KEY_MIDDLE_DRAG, KEY_RIGHT_DRAG, KEY_WHEEL_DOWN, KEY_WHEEL_UP,
+};
+use crate::hooks::keyboard::is_bound_mouse_key;
+use crate::win_api::window::send_event_message;
+use crate::{
+    win_api,
+    win_api::hook::{call_next_hook, set_window_hook},
+};
+
+// Mouse buttons whose press completed a keybind, so their release is swallowed as well
+static SWALLOWED_BUTTONS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+pub struct MouseHook {
+    hook: HHOOK,
+}
+
+impl MouseHook {
+    pub fn new() -> Self {
+        Self {
+            hook: HHOOK::default(),
+        }
+    }
+}
+unsafe impl Send for MouseHook {}
+unsafe impl Sync for MouseHook {}
+pub unsafe extern "system" fn callback(code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if code < 0 || code != HC_ACTION as i32 {
+        return call_next_hook(code, w_param, l_param);
+    }
+    let hook_struct: *mut MSLLHOOKSTRUCT = l_param.0 as *mut MSLLHOOKSTRUCT;
+    let mouse_hook_struct = hook_struct.as_ref().unwrap();
+    let message = w_param.0 as u32;
+    // Post the cursor position back to the main application message queue
+    if message == WM_MOUSEMOVE {
+        send_event_message(Event::mouse_move(mouse_hook_struct.pt));
+        return call_next_hook(code, w_param, l_param);
+    }
+    /*
+       Mouse buttons and the scroll wheel are posted as key events, but only when they complete a
+       keybind. Everything else keeps reaching the window under the cursor
+    */
+    match message {
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN => {
+            let button = button_key(message);
+            if is_bound_mouse_key(button) {
+                SWALLOWED_BUTTONS.lock().unwrap().push(button);
+                send_key_event(button, WM_KEYDOWN);
+                return LRESULT(1);
+            }
+        }
+        WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP => {
+            let button = button_key(message);
+            let mut swallowed_buttons = SWALLOWED_BUTTONS.lock().unwrap();
+            if swallowed_buttons.contains(&button) {
+                swallowed_buttons.retain(|swallowed| *swallowed != button);
+                send_key_event(button, WM_KEYUP);
+                return LRESULT(1);
+            }
+        }
+        WM_MOUSEWHEEL => {
+            // The high word holds the signed distance scrolled, positive away from the user
+            let wheel_key = match (mouse_hook_struct.mouseData >> 16) as i16 > 0 {
+                true => KEY_WHEEL_UP,
+                false => KEY_WHEEL_DOWN,
+            };
+            if is_bound_mouse_key(wheel_key) {
+                send_key_event(wheel_key, WM_KEYDOWN);
+                send_key_event(wheel_key, WM_KEYUP);
+                return LRESULT(1);
+            }
+        }
+        _ => {}
+    }
+    return call_next_hook(code, w_param, l_param);
+}
+
+fn button_key(message: u32) -> i32 {
+    return match message {
+        WM_LBUTTONDOWN | WM_LBUTTONUP => KEY_LEFT_DRAG,
+        WM_RBUTTONDOWN | WM_RBUTTONUP => KEY_RIGHT_DRAG,
+        _ => KEY_MIDDLE_DRAG,
+    };
+}
+
+fn send_key_event(key_code: i32, message: u32) {
+    send_event_message(Event::key_event(
+        key_code as isize,
+        0,
+        None,
+        WPARAM(message as usize),
+    ));
+}
+
+impl Hook for MouseHook {
+    fn set(&mut self) {
+        self.hook = set_window_hook(WH_MOUSE_LL, callback);
+    }
+
+    fn remove(&mut self) {
+        win_api::hook::unset_window_hook(self.hook);
+        self.hook = HHOOK::default();
+    }
+}
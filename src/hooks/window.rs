@@ -1,6 +1,6 @@
 use crate::data::common::event::Event;
 use windows::Win32::UI::WindowsAndMessaging::{
-    EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY, EVENT_SYSTEM_MINIMIZEEND,
+    EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY, EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_MINIMIZEEND,
 };
 use windows::Win32::{
     Foundation::HWND,
@@ -19,12 +19,14 @@ use crate::win_api::window::send_event_message;
 
 pub struct EventHook {
     hook: HWINEVENTHOOK,
+    title_hook: HWINEVENTHOOK,
 }
 
 impl EventHook {
     pub fn new() -> Self {
         Self {
             hook: HWINEVENTHOOK::default(),
+            title_hook: HWINEVENTHOOK::default(),
         }
     }
 }
@@ -32,11 +34,14 @@ impl EventHook {
 impl Hook for EventHook {
     fn set(&mut self) {
         self.hook = win_api::hook::set_event_hook(callback);
+        self.title_hook = win_api::hook::set_title_hook(callback);
     }
 
     fn remove(&mut self) {
         win_api::hook::unset_event_hook(self.hook);
         self.hook = HWINEVENTHOOK::default();
+        win_api::hook::unset_event_hook(self.title_hook);
+        self.title_hook = HWINEVENTHOOK::default();
     }
 }
 
@@ -65,6 +70,16 @@ pub unsafe extern "system" fn callback(
             }
             send_event_message(Event::destroy(hwnd));
         }
+        // Only the title of the foreground window decides which application keybinds apply
+        EVENT_OBJECT_NAMECHANGE => {
+            if object_id != OBJID_WINDOW.0
+                || child_id != CHILDID_SELF as i32
+                || hwnd != win_api::window::foreground_hwnd()
+            {
+                return;
+            }
+            send_event_message(Event::title_change(hwnd));
+        }
         _ => (),
     }
 }
@@ -1,14 +1,19 @@
 #![windows_subsystem = "windows"]
 use crate::data::common::event::{
-    CREATE, DESTROY, FOCUS, KEY_EVENT, MINIMIZE, MOVE_SIZE, RESTORE, WINDOW_EVENT,
+    key_from_lparam, point_from_lparam, CONFIG_EVENT, CREATE, DESTROY, FOCUS, KEYBINDINGS_EVENT,
+    KEY_EVENT, MINIMIZE, MOUSE_EVENT, MOVE_SIZE, RESTORE, TITLE_CHANGE, WINDOW_EVENT,
 };
 use crate::data::key::{Key, KeyEvent, KeyEventType};
+use crate::state::management::config_manager::ConfigManager;
 use crate::state::management::key_manager::KeyManager;
 use crate::state::management::state_manager::StateManager;
+use std::env;
+use std::process::exit;
 use windows::Win32::Foundation::HWND;
-use windows::Win32::UI::WindowsAndMessaging::{MSG, WM_NULL};
+use windows::Win32::UI::WindowsAndMessaging::{MSG, WM_NULL, WM_TIMER};
 
 mod actions;
+mod cli;
 mod config;
 mod data;
 mod hooks;
@@ -17,26 +22,61 @@ mod win_api;
 
 fn main() {
     env_logger::init();
-    let mut state_manager = StateManager::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        exit(exit_code);
+    }
+    // The config commands work anywhere, so configs can be checked on other platforms
+    if cfg!(not(windows)) {
+        eprintln!("Fenestra only runs on Windows, see 'fenestra check' for checking configs");
+        exit(1);
+    }
+    let config_path = state::init::config_path();
+    let config = state::init::config(&config_path);
+    let mut config_manager = ConfigManager::new(config_path, &config.settings);
+    let mut state_manager = StateManager::new(&config);
     state_manager.arrange_all_windows();
-    let mut key_manager = KeyManager::new();
+    let mut key_manager = KeyManager::new(config);
+    key_manager.update_bound_chords(&state_manager);
     let mut message = MSG::default();
     win_api::window::get_message(&mut message);
     while message.message != WM_NULL {
         win_api::window::get_message(&mut message);
         match message.message {
             KEY_EVENT => {
-                let key = Key::from(message.lParam.0 as i32);
+                let (key_code, scan_code, character) = key_from_lparam(message.lParam);
+                let key = Key::from(key_code);
                 let event_type = KeyEventType::from(message.wParam.0);
-                let key_event = KeyEvent::new(event_type, key);
+                let key_event = KeyEvent::new(event_type, key, scan_code, character);
                 key_manager.handle_keypress(key_event, &mut state_manager);
             }
+            MOUSE_EVENT => {
+                let point = point_from_lparam(message.lParam);
+                state_manager.handle_mouse_move(point);
+            }
+            WM_TIMER => {
+                key_manager.handle_timer(message.wParam.0, &mut state_manager);
+                state_manager.handle_timer(message.wParam.0);
+                if config_manager.handle_timer(message.wParam.0) {
+                    config_manager.reload(&mut key_manager, &mut state_manager);
+                }
+            }
+            CONFIG_EVENT => config_manager.reload(&mut key_manager, &mut state_manager),
+            KEYBINDINGS_EVENT => key_manager.show_keybindings(&mut state_manager),
             WINDOW_EVENT => {
                 let hwnd = HWND(message.lParam.0);
                 if state_manager.ignore_events {
                     continue;
                 }
+                // A new title may change which application keybinds apply, nothing else to arrange
+                if message.wParam.0 == TITLE_CHANGE {
+                    state_manager.window_manager.update_title(&hwnd);
+                    key_manager.update_bound_chords(&state_manager);
+                    continue;
+                }
                 match message.wParam.0 {
+                    // Floating and unmanaged windows are left alone
+                    MINIMIZE | RESTORE | MOVE_SIZE if !state_manager.is_tiled(&hwnd) => {}
                     MINIMIZE => {
                         state_manager.window_manager.minimize(&hwnd);
                         let group = state_manager.group_manager.group_for_hwnd(&hwnd);
@@ -88,7 +128,12 @@ fn main() {
                             .calculate_window_positions(updated_groups, &manageable_windows);
                         state_manager.arrange_windows(new_positions);
                     }
-                    FOCUS | CREATE => state_manager.add_window(hwnd),
+                    FOCUS => {
+                        key_manager.update_bound_chords(&state_manager);
+                        state_manager.add_window(hwnd);
+                        state_manager.update_overlays();
+                    }
+                    CREATE => state_manager.add_window(hwnd),
                     DESTROY => state_manager.remove_window(hwnd),
                     _ => {}
                 }
@@ -1,19 +1,25 @@
 mod monitors;
 
+use crate::config::lexer::ConfigError;
+use crate::config::profile::Machine;
 use crate::data::common::state::AppState;
+use crate::data::config::{Config, Settings};
 use crate::data::hook::Hook;
-use crate::data::key::Keybind;
 use crate::data::monitor::Monitor;
 use crate::win_api::window::set_dpi_awareness;
 use crate::{config, hooks, win_api};
-use std::path::Path;
+use log::{error, warn};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 use windows::Win32::Foundation::{HMODULE, HWND};
 use windows::Win32::UI::WindowsAndMessaging::{
-    CW_USEDEFAULT, WINDOW_EX_STYLE, WS_OVERLAPPEDWINDOW,
+    CW_USEDEFAULT, WINDOW_EX_STYLE, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+    WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_OVERLAPPEDWINDOW, WS_POPUP,
 };
 
-pub fn application() -> AppState {
-    AppState::new(window(), hooks())
+pub fn application(settings: &Settings) -> AppState {
+    AppState::new(window(), hooks(settings))
 }
 
 fn window() -> HWND {
@@ -36,15 +42,82 @@ fn window() -> HWND {
     win_api::window::system_tray(&hwnd);
     return hwnd;
 }
-fn hooks() -> Vec<Box<dyn Hook>> {
-    hooks::set_hooks()
+// Click-through, always on top window used to draw decorations such as the active window border
+pub fn overlay_window() -> HWND {
+    let app_instance: HMODULE = win_api::misc::get_main_module();
+    const OVERLAY_NAME: &str = "FenestraOverlay\0";
+    win_api::overlay::register_class(app_instance, OVERLAY_NAME);
+
+    let hwnd: HWND = win_api::window::create_window(
+        WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW | WS_EX_TOPMOST | WS_EX_NOACTIVATE,
+        OVERLAY_NAME,
+        OVERLAY_NAME,
+        WS_POPUP,
+        0,
+        0,
+        0,
+        0,
+        app_instance,
+    );
+    win_api::overlay::set_transparent_color(hwnd);
+    return hwnd;
+}
+
+fn hooks(settings: &Settings) -> Vec<Box<dyn Hook>> {
+    hooks::set_hooks(settings)
+}
+
+pub fn config_path() -> PathBuf {
+    let args: Vec<String> = env::args().skip(1).collect();
+    return config::load::locate(&args);
+}
+
+pub fn config(config_path: &Path) -> Config {
+    let config = config::load::ensure_exists(config_path)
+        .and_then(|_| config::parse::parse_content(config_path, &machine()));
+    let config: Config = match config {
+        Ok(config) => config,
+        Err(errors) => {
+            errors.iter().for_each(|config_error| error!("{}", config_error));
+            show_config_errors(config_path, &errors);
+            exit(1);
+        }
+    };
+    config
+        .warnings
+        .iter()
+        .for_each(|config_warning| warn!("{}", config_warning));
+    return config;
+}
+
+// Only the first errors are listed, a message box doesn't scroll
+const MAX_SHOWN_ERRORS: usize = 20;
+
+fn show_config_errors(config_path: &Path, errors: &[ConfigError]) {
+    let mut message = format!(
+        "Fenestra can't start, {} has {} error(s):\n\n",
+        config_path.display(),
+        errors.len()
+    );
+    for config_error in errors.iter().take(MAX_SHOWN_ERRORS) {
+        message.push_str(&format!("{}\n", config_error));
+    }
+    if errors.len() > MAX_SHOWN_ERRORS {
+        message.push_str(&format!(
+            "...and {} more, 'fenestra check' lists all of them\n",
+            errors.len() - MAX_SHOWN_ERRORS
+        ));
+    }
+    win_api::window::show_error("Fenestra", &message);
 }
 
-pub fn keybinds() -> Vec<Keybind> {
-    let config_path: &Path = Path::new("./fenestra.conf");
-    config::load::ensure_exists(config_path);
-    let configured_key_binds: Vec<Keybind> = config::parse::parse_content(config_path);
-    return configured_key_binds;
+// Conditional blocks of the config are checked against this machine and its connected monitors
+pub fn machine() -> Machine {
+    let monitors = win_api::monitor::get_all()
+        .into_iter()
+        .map(|monitor| monitor.name)
+        .collect();
+    return Machine::new(monitors);
 }
 
 pub fn monitors() -> Vec<Monitor> {
@@ -0,0 +1,77 @@
+use windows::Win32::Foundation::{HWND, RECT};
+
+use crate::data::border::{border_geometry, BorderStyle};
+use crate::state::init;
+use crate::win_api;
+use crate::win_api::overlay::OverlayContent;
+
+pub struct BorderManager {
+    overlay: HWND,
+    style: BorderStyle,
+    tracked: Option<HWND>,
+}
+
+impl BorderManager {
+    pub fn new(overlay: HWND, style: BorderStyle) -> Self {
+        Self {
+            overlay,
+            style,
+            tracked: None,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.style.enabled()
+    }
+
+    // The overlay only exists while a border is enabled
+    pub fn set_style(&mut self, style: BorderStyle) {
+        let created = self.overlay != HWND::default();
+        if !style.enabled() && created {
+            win_api::overlay::destroy(self.overlay);
+            self.overlay = HWND::default();
+            self.tracked = None;
+        } else if style.enabled() && !created {
+            self.overlay = init::overlay_window();
+        }
+        self.style = style;
+    }
+
+    /*
+       Determine which window should carry the border. The focused tiled window is drawn as active,
+       while the last focused tiled window keeps an inactive border when focus moves elsewhere
+    */
+    pub fn target(&mut self, foreground: HWND, managed_hwnds: &[HWND]) -> Option<(HWND, bool)> {
+        if managed_hwnds.contains(&foreground) {
+            self.tracked = Some(foreground);
+            return Some((foreground, true));
+        }
+        let tracked = self.tracked?;
+        if !managed_hwnds.contains(&tracked) {
+            self.tracked = None;
+            return None;
+        }
+        return Some((tracked, false));
+    }
+
+    pub fn show(&self, rect: &RECT, shadow_rect: &RECT, dpi: u32, active: bool) {
+        match border_geometry(rect, shadow_rect, self.style.width, dpi) {
+            Some(geometry) => {
+                win_api::overlay::set_content(
+                    self.overlay,
+                    OverlayContent::Border {
+                        color: self.style.color(active),
+                        width: geometry.width,
+                        rounded: self.style.rounded,
+                    },
+                );
+                win_api::overlay::show(self.overlay, geometry.outer);
+            }
+            None => self.hide(),
+        }
+    }
+
+    pub fn hide(&self) {
+        win_api::overlay::hide(self.overlay);
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log::{error, info, warn};
+
+use crate::config;
+use crate::data::config::Settings;
+use crate::state::init;
+use crate::state::management::key_manager::KeyManager;
+use crate::state::management::state_manager::StateManager;
+use crate::win_api;
+
+// How often the config file is checked for changes while watch_config is on
+const WATCH_INTERVAL: u32 = 1000;
+
+// Re-reads the config file on request, or whenever it changes while it is being watched
+pub struct ConfigManager {
+    path: PathBuf,
+    // The config file and the files it includes, with their modification time when last read
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    timer: usize,
+}
+
+impl ConfigManager {
+    pub fn new(path: PathBuf, settings: &Settings) -> Self {
+        let mut config_manager = Self {
+            path,
+            files: Vec::new(),
+            timer: 0,
+        };
+        config_manager.remember_files();
+        config_manager.watch(settings.watch_config);
+        return config_manager;
+    }
+
+    // Returns true when the watch timer found a file changed since it was last read
+    pub fn handle_timer(&mut self, timer_id: usize) -> bool {
+        if timer_id == 0 || timer_id != self.timer {
+            return false;
+        }
+        return self
+            .files
+            .iter()
+            .any(|(path, modified)| modified_time(path) != *modified);
+    }
+
+    fn remember_files(&mut self) {
+        self.files = config::profile::config_files(&self.path, &init::machine())
+            .into_iter()
+            .map(|path| {
+                let modified = modified_time(&path);
+                (path, modified)
+            })
+            .collect();
+    }
+
+    /*
+       Parse the config again and swap in its keybinds and settings. A config with errors is not
+       applied at all, the current one stays active
+    */
+    pub fn reload(&mut self, key_manager: &mut KeyManager, state_manager: &mut StateManager) {
+        // Remembered up front so a broken file isn't parsed again until it is saved again
+        self.remember_files();
+        let config = match config::parse::parse_content(&self.path, &init::machine()) {
+            Ok(config) => config,
+            Err(errors) => {
+                errors
+                    .iter()
+                    .for_each(|config_error| error!("{}", config_error));
+                error!(
+                    "Keeping the current config, {} has {} error(s)",
+                    self.path.display(),
+                    errors.len()
+                );
+                return;
+            }
+        };
+        info!("Reloading config from {}", self.path.display());
+        config
+            .warnings
+            .iter()
+            .for_each(|config_warning| warn!("{}", config_warning));
+        self.watch(config.settings.watch_config);
+        let settings = config.settings.clone();
+        key_manager.replace_keybinds(config);
+        // A table left on screen would list the old keybinds
+        state_manager.keybindings_manager.hide();
+        if let Some(mode) = state_manager.current_mode() {
+            if !key_manager.has_mode(mode) {
+                state_manager.exit_mode();
+            }
+        }
+        state_manager.apply_settings(settings);
+        key_manager.update_bound_chords(state_manager);
+    }
+
+    fn watch(&mut self, enabled: bool) {
+        if enabled && self.timer == 0 {
+            self.timer = win_api::window::set_timer(0, WATCH_INTERVAL);
+        } else if !enabled && self.timer != 0 {
+            win_api::window::kill_timer(self.timer);
+            self.timer = 0;
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    return fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+}
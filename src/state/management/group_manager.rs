@@ -1,15 +1,30 @@
 use crate::data::common::axis::Axis;
 use crate::data::common::direction::Direction;
-use crate::data::group::Group;
+use crate::data::group::{tab_strip, Group, Layout};
 use windows::Win32::Foundation::{HWND, RECT};
 
+const MIN_WEIGHT: f32 = 0.1;
+
 pub struct GroupManager {
     groups: Vec<Group>,
+    // Height reserved above stacked groups for the tab strip, 0 when tabs are disabled
+    tab_height: i32,
 }
 
 impl GroupManager {
-    pub fn new(groups: Vec<Group>) -> Self {
-        Self { groups }
+    pub fn new(groups: Vec<Group>, tab_height: i32) -> Self {
+        Self { groups, tab_height }
+    }
+
+    pub fn set_tab_height(&mut self, tab_height: i32) {
+        self.tab_height = tab_height;
+    }
+
+    // Takes effect on the next arrangement of the group
+    pub fn set_area(&mut self, group_index: usize, rect: RECT, split_axis: Axis) {
+        let group = self.get_group(group_index);
+        group.rect = rect;
+        group.split_axis = split_axis;
     }
 
     pub fn managed_hwnds(&self) -> Vec<&HWND> {
@@ -104,6 +119,7 @@ impl GroupManager {
         let group_index = self.get_group_index_by_hwnd(*hwnd);
         let group = self.get_group(group_index);
         group.windows.retain(|h| h.0 != hwnd.0);
+        group.weights.remove(&hwnd.0);
         return self
             .calculate_window_positions(vec![group_index], &self.groups[group_index].windows);
     }
@@ -158,6 +174,16 @@ impl GroupManager {
         // debug!("Calculating window positions for {} groups", num_groups);
         for group_id in group_ids {
             let group = &self.groups[group_id];
+            if group.layout == Layout::STACKED {
+                // Every window in the stack shares the area below the tab strip
+                let (_, content) = tab_strip(&group.rect, self.tab_height);
+                group
+                    .windows
+                    .iter()
+                    .filter(|hwnd| manageable_hwnds.contains(hwnd))
+                    .for_each(|hwnd| window_positions.push((*hwnd, content)));
+                continue;
+            }
             let group_width = group.rect.right - group.rect.left;
             let rect_height = group.rect.bottom - group.rect.top;
             // let group_width = rect_width as f32 / num_groups as f32;
@@ -166,40 +192,28 @@ impl GroupManager {
                 .iter()
                 .filter(|hwnd| manageable_hwnds.contains(hwnd))
                 .collect();
-            let num_windows = windows.len();
-            let (section_width, section_height) = match group.split_axis {
-                Axis::HORIZONTAL => (
-                    group_width,
-                    (rect_height as f32 / num_windows as f32) as i32,
-                ),
-                Axis::VERTICAL => (
-                    (group_width as f32 / num_windows as f32) as i32,
-                    (rect_height as f32) as i32,
-                ),
+            // Each window takes a share of the group proportional to its weight
+            let total_weight: f32 = windows.iter().map(|hwnd| group.weight(hwnd)).sum();
+            let group_length = match group.split_axis {
+                Axis::HORIZONTAL => rect_height,
+                Axis::VERTICAL => group_width,
             };
-            // debug!(
-            //     "Group width {} / {} windows = {} window width",
-            //     group_width, num_windows, section_width
-            // );
-            for window_index in 0..num_windows {
-                let hwnd = windows[window_index];
+            let mut offset: f32 = 0.0;
+            for hwnd in windows {
+                let start = offset as i32;
+                offset += group_length as f32 * group.weight(hwnd) / total_weight;
+                let end = offset as i32;
                 let new_position = match group.split_axis {
-                    Axis::HORIZONTAL => {
-                        let top = group.rect.top + (section_height * window_index as i32);
-                        RECT {
-                            top,
-                            bottom: top + section_height,
-                            ..group.rect
-                        }
-                    }
-                    Axis::VERTICAL => {
-                        let left = group.rect.left + (section_width * window_index as i32);
-                        RECT {
-                            left,
-                            right: left + section_width,
-                            ..group.rect
-                        }
-                    }
+                    Axis::HORIZONTAL => RECT {
+                        top: group.rect.top + start,
+                        bottom: group.rect.top + end,
+                        ..group.rect
+                    },
+                    Axis::VERTICAL => RECT {
+                        left: group.rect.left + start,
+                        right: group.rect.left + end,
+                        ..group.rect
+                    },
                 };
                 window_positions.push((*hwnd, new_position));
             }
@@ -231,6 +245,98 @@ impl GroupManager {
             .expect("Unable to fetch hwnd index within group")
     }
 
+    // Grow (or shrink, with a negative delta) a window's share of its group
+    pub fn resize_window(&mut self, hwnd: &HWND, delta: f32) -> usize {
+        let group_index = self.get_group_index_by_hwnd(*hwnd);
+        let group = self.get_group(group_index);
+        let weight = (group.weight(hwnd) + delta).max(MIN_WEIGHT);
+        group.weights.insert(hwnd.0, weight);
+        return group_index;
+    }
+
+    /*
+       Move the edge between a window and its neighbour by the given pixels along the group's split
+       axis. Positive pixels move the edge right or down, which grows every window but the last one
+    */
+    pub fn move_window_edge(
+        &mut self,
+        hwnd: &HWND,
+        pixels: (i32, i32),
+        manageable_hwnds: &[HWND],
+    ) -> usize {
+        let group_index = self.get_group_index_by_hwnd(*hwnd);
+        let group = self.get_group(group_index);
+        let windows: Vec<HWND> = group
+            .windows
+            .iter()
+            .filter(|h| manageable_hwnds.contains(h))
+            .cloned()
+            .collect();
+        let (length, mut pixels) = match group.split_axis {
+            Axis::HORIZONTAL => (group.rect.bottom - group.rect.top, pixels.1),
+            Axis::VERTICAL => (group.rect.right - group.rect.left, pixels.0),
+        };
+        if windows.len() < 2 || length <= 0 {
+            return group_index;
+        }
+        // The last window has no edge after it, dragging it moves the edge before it instead
+        if windows.last() == Some(hwnd) {
+            pixels = -pixels;
+        }
+        let total_weight: f32 = windows.iter().map(|h| group.weight(h)).sum();
+        let weight = group.weight(hwnd);
+        let other_weight = total_weight - weight;
+        let size = length as f32 * weight / total_weight;
+        let new_size = (size + pixels as f32).clamp(1.0, length as f32 - 1.0);
+        // Keep the other windows' weights, and pick the one that gives this window its new size
+        let new_weight = other_weight * new_size / (length as f32 - new_size);
+        group.weights.insert(hwnd.0, new_weight.max(MIN_WEIGHT));
+        return group_index;
+    }
+
+    pub fn is_stacked(&self, group_index: usize) -> bool {
+        self.groups[group_index].layout == Layout::STACKED
+    }
+
+    pub fn stacked_groups(&self) -> Vec<usize> {
+        self.groups
+            .iter()
+            .filter(|group| group.layout == Layout::STACKED)
+            .map(|group| group.index)
+            .collect()
+    }
+
+    pub fn toggle_stacking(&mut self, group_index: usize) -> Layout {
+        let group = self.get_group(group_index);
+        group.layout = match group.layout {
+            Layout::SPLIT => Layout::STACKED,
+            Layout::STACKED => Layout::SPLIT,
+        };
+        return group.layout;
+    }
+
+    pub fn tab_strip_rect(&self, group_index: usize) -> RECT {
+        let (strip, _) = tab_strip(&self.groups[group_index].rect, self.tab_height);
+        return strip;
+    }
+
+    // The window after the given one within its group, wrapping around at the end
+    pub fn next_in_group(&self, hwnd: &HWND, managed_hwnds: &[HWND]) -> Option<HWND> {
+        let group_index = self.group_for_hwnd(hwnd);
+        let hwnds: Vec<HWND> = self.groups[group_index]
+            .windows
+            .iter()
+            .filter(|h| managed_hwnds.contains(h))
+            .cloned()
+            .collect();
+        let position = hwnds.iter().position(|h| h == hwnd)?;
+        let next = hwnds[(position + 1) % hwnds.len()];
+        if next == *hwnd {
+            return None;
+        }
+        return Some(next);
+    }
+
     pub fn group_is_axis(&self, group_index: usize, axis: &Axis) -> bool {
         self.groups[group_index].split_axis == *axis
     }
@@ -1,47 +1,302 @@
-use crate::data::action::Action;
-use crate::data::key::KeyEventType::{PRESS, RELEASE};
-use crate::data::key::{Key, KeyEvent, Keybind};
-use crate::state::init;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::data::action::{Action, WindowManagerAction};
+use crate::data::config::Config;
+use crate::data::key::KeyEventType::RELEASE;
+use crate::data::key::{is_mouse_button, KeyEvent, Keybind};
+use crate::data::sequence::{Chord, SequenceMatcher, Trigger};
+use crate::data::window::WindowMetadata;
 use crate::state::management::state_manager::StateManager;
+use crate::{hooks, win_api};
 
 pub struct KeyManager {
     keybinds: Vec<Keybind>,
-    pressed_keys: Vec<Key>,
+    matcher: SequenceMatcher,
+    // Thread timer flushing a partial key sequence once it times out
+    timer: usize,
+    // Keybinds paused by a delay, resumed from the given action index once the instant passes
+    delayed: Vec<(Instant, usize, usize)>,
+    delay_timer: usize,
 }
 
 impl KeyManager {
-    pub fn new() -> Self {
+    // Takes the keybinds of the config, the managers reading its settings have to come first
+    pub fn new(config: Config) -> Self {
+        let keybinds = config.keybinds;
+        let sequence_timeout = config.settings.sequence_timeout;
+        let sequences: Vec<(Option<String>, Vec<Chord>, Trigger)> = keybinds
+            .iter()
+            .map(|keybind| {
+                let sequence = keybind
+                    .sequence
+                    .iter()
+                    .map(|chord| chord.iter().map(|key| key.code).collect())
+                    .collect();
+                (keybind.mode.clone(), sequence, keybind.trigger)
+            })
+            .collect();
+        let matcher =
+            SequenceMatcher::new(sequences, Duration::from_millis(sequence_timeout as u64));
         Self {
-            keybinds: init::keybinds(),
-            pressed_keys: Vec::new(),
+            keybinds,
+            matcher,
+            timer: 0,
+            delayed: Vec::new(),
+            delay_timer: 0,
         }
     }
 
-    // Returns a bool based on if the keypress should be propagated to the rest of the key listeners
+    /*
+       Swap in the keybinds of a reloaded config. Partial sequences and delayed actions refer to the
+       old keybinds, so they are dropped along with their timers
+    */
+    pub fn replace_keybinds(&mut self, config: Config) {
+        for timer in [self.timer, self.delay_timer] {
+            if timer != 0 {
+                win_api::window::kill_timer(timer);
+            }
+        }
+        *self = KeyManager::new(config);
+    }
+
+    // Whether any keybind belongs to the binding mode
+    pub fn has_mode(&self, mode: &str) -> bool {
+        return self
+            .keybinds
+            .iter()
+            .any(|keybind| keybind.mode.as_deref() == Some(mode));
+    }
+
     pub fn handle_keypress(&mut self, key_press: KeyEvent, state_manager: &mut StateManager) {
-        match key_press.event {
-            PRESS => {
-                // User pressed a key, add it to pressed_keys
-                if !self.pressed_keys.contains(&key_press.key) {
-                    self.pressed_keys.push(Key::from(key_press.key.code));
+        // Releasing the mouse button ends a drag started by one of its keybinds
+        if matches!(key_press.event, RELEASE) && is_mouse_button(key_press.key.code) {
+            state_manager.end_drag();
+        }
+        // Only the keybinds of the active binding mode apply
+        let mode = state_manager.current_mode().map(String::from);
+        let fired = self
+            .matcher
+            .handle(&key_press, mode.as_deref(), Instant::now());
+        let metadata = self.foreground_metadata(state_manager);
+        let fired = self.resolve(&fired, &metadata);
+        self.execute(fired, state_manager);
+    }
+
+    pub fn handle_timer(&mut self, timer_id: usize, state_manager: &mut StateManager) {
+        if timer_id != 0 && timer_id == self.delay_timer {
+            self.resume_delayed(state_manager);
+            return;
+        }
+        if timer_id == 0 || timer_id != self.timer {
+            return;
+        }
+        let fired = self.matcher.expire(Instant::now());
+        let metadata = self.foreground_metadata(state_manager);
+        let fired = self.resolve(&fired, &metadata);
+        self.execute(fired, state_manager);
+    }
+
+    fn execute(&mut self, fired: Vec<usize>, state_manager: &mut StateManager) {
+        for index in fired {
+            self.run_actions(index, 0, state_manager);
+        }
+        // Restart the timeout whenever a partial sequence is held
+        if self.matcher.is_pending() {
+            let timeout = self.matcher.timeout().as_millis() as u32;
+            self.timer = win_api::window::set_timer(self.timer, timeout);
+        } else if self.timer != 0 {
+            win_api::window::kill_timer(self.timer);
+            self.timer = 0;
+        }
+        self.update_bound_chords(state_manager);
+    }
+
+    // Show the table of keybinds on the current monitor, or hide it when it is already shown
+    pub fn show_keybindings(&self, state_manager: &mut StateManager) {
+        let work_area = state_manager
+            .monitor_manager
+            .work_area(state_manager.current_monitor());
+        state_manager
+            .keybindings_manager
+            .toggle(config::bindings::lines(&self.keybinds), work_area);
+    }
+
+    /*
+       Let the keyboard hook know which keys to keep from reaching other applications. Depends on the
+       foreground window, as keys bound to pass_through there are left to the application
+    */
+    pub fn update_bound_chords(&self, state_manager: &StateManager) {
+        let metadata = self.foreground_metadata(state_manager);
+        let chords = self
+            .matcher
+            .bound_chords(state_manager.current_mode(), |bindings| {
+                self.is_swallowed(bindings, &metadata)
+            });
+        hooks::keyboard::set_bound_chords(chords);
+    }
+
+    // Keys are swallowed unless every keybind applying to the window only passes them through
+    fn is_swallowed(&self, bindings: &[usize], metadata: &WindowMetadata) -> bool {
+        return self.resolve(bindings, metadata).iter().any(|index| {
+            !self.keybinds[*index]
+                .actions
+                .iter()
+                .all(|action| matches!(action, WindowManagerAction::PassThrough(_)))
+        });
+    }
+
+    /*
+       Keep the bindings which apply to the foreground window. Application keybinds replace the global
+       keybinds of the same keys while their application is in the foreground
+    */
+    fn resolve(&self, fired: &[usize], metadata: &WindowMetadata) -> Vec<usize> {
+        let applies = |index: &usize| match &self.keybinds[*index].rule {
+            Some(rule) => rule.matches(metadata),
+            None => false,
+        };
+        return fired
+            .iter()
+            .filter(|index| {
+                let keybind = &self.keybinds[**index];
+                match keybind.rule {
+                    Some(_) => applies(index),
+                    None => !fired.iter().any(|other| {
+                        applies(other) && self.keybinds[*other].sequence == keybind.sequence
+                    }),
                 }
+            })
+            .cloned()
+            .collect();
+    }
+
+    // Only look up the foreground window when there are application keybinds to match it against
+    fn foreground_metadata(&self, state_manager: &StateManager) -> WindowMetadata {
+        if self.keybinds.iter().all(|keybind| keybind.rule.is_none()) {
+            return WindowMetadata::default();
+        }
+        return state_manager.foreground_metadata();
+    }
+
+    // Execute the actions of a keybind in order, until the next delay step
+    fn run_actions(&mut self, keybind: usize, start: usize, state_manager: &mut StateManager) {
+        let actions = &self.keybinds[keybind].actions;
+        for (index, action) in actions.iter().enumerate().skip(start) {
+            if let WindowManagerAction::Delay(delay) = action {
+                self.delayed
+                    .push((Instant::now() + delay.duration, keybind, index + 1));
+                self.schedule_delayed();
+                return;
             }
-            RELEASE => {
-                // Attempt to find a matching keybind
-                for keybind in self.keybinds.iter() {
-                    /*
-                       Sort the keys before comparing to account for the
-                       user pressing the correct keys in a different order
-                    */
-                    self.pressed_keys.sort();
-                    if keybind.keys == *self.pressed_keys {
-                        // Execute the action associated with the keybind
-                        keybind.action.execute(state_manager);
-                    }
-                }
-                // User released a key, remove it from pressed_keys
-                self.pressed_keys.retain(|key| key != &key_press.key);
+            action.execute(state_manager);
+        }
+    }
+
+    fn resume_delayed(&mut self, state_manager: &mut StateManager) {
+        let now = Instant::now();
+        let (due, waiting): (Vec<_>, Vec<_>) = self
+            .delayed
+            .drain(..)
+            .partition(|(resume_at, _, _)| *resume_at <= now);
+        self.delayed = waiting;
+        for (_, keybind, start) in due {
+            self.run_actions(keybind, start, state_manager);
+        }
+        self.schedule_delayed();
+    }
+
+    // Point the delay timer at the keybind due to resume first
+    fn schedule_delayed(&mut self) {
+        match self
+            .delayed
+            .iter()
+            .map(|(resume_at, _, _)| *resume_at)
+            .min()
+        {
+            Some(resume_at) => {
+                let wait = resume_at.saturating_duration_since(Instant::now());
+                self.delay_timer =
+                    win_api::window::set_timer(self.delay_timer, wait.as_millis() as u32);
+            }
+            None if self.delay_timer != 0 => {
+                win_api::window::kill_timer(self.delay_timer);
+                self.delay_timer = 0;
             }
+            None => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::parse::parse_str;
+    use crate::config::profile::Machine;
+    use crate::data::window::WindowMetadata;
+    use crate::state::management::key_manager::KeyManager;
+
+    // Keybinds 0 and 1 share their keys, as do 2 and 3
+    const CONFIG: &str = "\
+[bindings]
+close_window: WIN + q
+toggle_sticky: WIN + q [exe=firefox.exe]
+pass_through: WIN + w [exe=vmconnect.exe]
+toggle_scratchpad: WIN + w
+show_keybindings: WIN + k [title=Notes]
+";
+
+    fn manager() -> KeyManager {
+        let config = parse_str(Path::new("test.conf"), CONFIG, &Machine::default()).unwrap();
+        return KeyManager::new(config);
+    }
+
+    fn window(executable: &str, title: &str) -> WindowMetadata {
+        return WindowMetadata {
+            executable: String::from(executable),
+            class_name: String::from("Window"),
+            title: String::from(title),
+        };
+    }
+
+    #[test]
+    fn application_keybinds_override_global_ones() {
+        let manager = manager();
+        let firefox = window("firefox.exe", "Mozilla Firefox");
+        assert_eq!(manager.resolve(&[0, 1], &firefox), vec![1]);
+        let uppercase = window("FIREFOX.EXE", "Mozilla Firefox");
+        assert_eq!(manager.resolve(&[0, 1], &uppercase), vec![1]);
+    }
+
+    #[test]
+    fn global_keybinds_apply_to_other_applications() {
+        let manager = manager();
+        let notepad = window("notepad.exe", "Untitled - Notepad");
+        assert_eq!(manager.resolve(&[0, 1], &notepad), vec![0]);
+        assert_eq!(manager.resolve(&[2, 3], &notepad), vec![3]);
+        assert!(manager.is_swallowed(&[2, 3], &notepad));
+        // Keybinds without a global fallback don't apply outside their application
+        assert!(manager.resolve(&[4], &notepad).is_empty());
+        assert!(!manager.is_swallowed(&[4], &notepad));
+    }
+
+    #[test]
+    fn pass_through_leaves_keys_to_the_application() {
+        let manager = manager();
+        let vmconnect = window("vmconnect.exe", "Virtual Machine Connection");
+        assert_eq!(manager.resolve(&[2, 3], &vmconnect), vec![2]);
+        assert!(!manager.is_swallowed(&[2, 3], &vmconnect));
+    }
+
+    #[test]
+    fn title_rules_follow_the_current_title() {
+        let manager = manager();
+        assert_eq!(
+            manager.resolve(&[4], &window("notepad.exe", "notes.txt - Notepad")),
+            vec![4]
+        );
+        assert!(manager
+            .resolve(&[4], &window("notepad.exe", "todo.txt - Notepad"))
+            .is_empty());
+    }
+}
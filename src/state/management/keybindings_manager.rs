@@ -0,0 +1,45 @@
+use windows::Win32::Foundation::{HWND, RECT};
+
+use crate::data::workspace::centered_size;
+use crate::state::init;
+use crate::win_api;
+use crate::win_api::overlay::OverlayContent;
+
+// Draws the keybinds of the config in the middle of the screen, toggled by show_keybindings
+pub struct KeybindingsManager {
+    overlay: HWND,
+    shown: bool,
+}
+
+impl KeybindingsManager {
+    pub fn new() -> Self {
+        Self {
+            overlay: HWND::default(),
+            shown: false,
+        }
+    }
+
+    // The overlay is only created the first time the keybinds are shown
+    pub fn toggle(&mut self, lines: Vec<String>, work_area: RECT) {
+        if self.shown {
+            self.hide();
+            return;
+        }
+        if self.overlay == HWND::default() {
+            self.overlay = init::overlay_window();
+        }
+        // Long tables are cut off at the edges of the work area
+        let (width, height) = win_api::overlay::text_size(&lines);
+        let position = centered_size(&work_area, width, height);
+        win_api::overlay::set_content(self.overlay, OverlayContent::Text { lines });
+        win_api::overlay::show(self.overlay, position);
+        self.shown = true;
+    }
+
+    pub fn hide(&mut self) {
+        if self.shown {
+            win_api::overlay::hide(self.overlay);
+        }
+        self.shown = false;
+    }
+}
@@ -0,0 +1,65 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use log::{debug, error};
+
+use crate::actions::exec::{split_command_line, Exec};
+
+// Programs which haven't opened a window by then are no longer routed to their workspace
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct PendingLaunch {
+    process_id: u32,
+    workspace: usize,
+    started: Instant,
+}
+
+pub struct LaunchManager {
+    pending: Vec<PendingLaunch>,
+}
+
+impl LaunchManager {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+
+    // Start the program, remembering the workspace its first window should open on
+    pub fn spawn(&mut self, exec: &Exec, workspace: Option<usize>) {
+        let arguments = split_command_line(&exec.command);
+        if arguments.is_empty() {
+            return;
+        }
+        let mut command = Command::new(&arguments[0]);
+        command.args(&arguments[1..]);
+        if let Some(cwd) = &exec.cwd {
+            command.current_dir(cwd);
+        }
+        command.envs(exec.env.iter().map(|(name, value)| (name, value)));
+        match command.spawn() {
+            Ok(child) => {
+                debug!("Started '{}' ({})", exec.command, child.id());
+                if let Some(workspace) = workspace {
+                    self.pending.push(PendingLaunch {
+                        process_id: child.id(),
+                        workspace,
+                        started: Instant::now(),
+                    });
+                }
+            }
+            Err(error) => error!("Failed to start '{}': {}", exec.command, error),
+        }
+    }
+
+    // Returns the target workspace when the window belongs to a program started with one
+    pub fn claim(&mut self, process_id: u32, now: Instant) -> Option<usize> {
+        self.pending
+            .retain(|launch| now.duration_since(launch.started) < LAUNCH_TIMEOUT);
+        let index = self
+            .pending
+            .iter()
+            .position(|launch| launch.process_id == process_id)?;
+        return Some(self.pending.remove(index).workspace);
+    }
+}
@@ -1,6 +1,12 @@
+mod border_manager;
+pub mod config_manager;
 mod group_manager;
 pub mod key_manager;
+mod keybindings_manager;
+mod launch_manager;
 mod monitor_manager;
+mod mouse_manager;
+mod stack_manager;
 pub mod state_manager;
 mod window_manager;
 mod workspace_manager;
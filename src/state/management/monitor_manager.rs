@@ -1,7 +1,7 @@
 use crate::data::common::direction::Direction;
 use crate::data::monitor::Monitor;
 use crate::win_api;
-use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::{HWND, RECT};
 use windows::Win32::Graphics::Gdi::HMONITOR;
 
 pub struct MonitorManager {
@@ -13,6 +13,10 @@ impl MonitorManager {
         Self { monitors }
     }
 
+    pub fn monitors(&self) -> &Vec<Monitor> {
+        &self.monitors
+    }
+
     pub fn monitor_from_hwnd(&self, hwnd: &HWND) -> HMONITOR {
         win_api::monitor::hmonitor_from_hwnd(*hwnd)
     }
@@ -42,6 +46,14 @@ impl MonitorManager {
         monitor.neighbors.get(direction).cloned()
     }
 
+    pub fn work_area(&self, hmonitor: HMONITOR) -> RECT {
+        self.monitors
+            .iter()
+            .find(|monitor| monitor.hmonitor == hmonitor)
+            .map(|monitor| monitor.info.rcWork)
+            .expect("Unable to find monitor for requested hmonitor")
+    }
+
     pub fn workspaces_for_monitor(&self, hmonitor: HMONITOR) -> &Vec<usize> {
         let monitor = self
             .monitors
@@ -0,0 +1,279 @@
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::{HWND, POINT, RECT};
+
+use crate::data::config::Settings;
+use crate::data::drag::{Drag, DragKind};
+
+pub struct MouseManager {
+    focus_follows_mouse: bool,
+    mouse_follows_focus: bool,
+    delay: Duration,
+    hovered: Option<(HWND, Instant)>,
+    pub timer: usize,
+    pub drag: Option<Drag>,
+}
+
+impl MouseManager {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            focus_follows_mouse: settings.focus_follows_mouse,
+            mouse_follows_focus: settings.mouse_follows_focus,
+            delay: Duration::from_millis(settings.focus_follows_mouse_delay as u64),
+            hovered: None,
+            timer: 0,
+            drag: None,
+        }
+    }
+
+    // Keeps an ongoing hover or drag going
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.focus_follows_mouse = settings.focus_follows_mouse;
+        self.mouse_follows_focus = settings.mouse_follows_focus;
+        self.delay = Duration::from_millis(settings.focus_follows_mouse_delay as u64);
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    // A timer that isn't the focus delay timer belongs to someone else
+    pub fn timer_fired(&mut self, timer_id: usize) -> bool {
+        if timer_id == 0 || timer_id != self.timer {
+            return false;
+        }
+        self.timer = 0;
+        return true;
+    }
+
+    // Returns true when the cursor entered a new window and the delay timer should be (re)started
+    pub fn hover(&mut self, hwnd: HWND, now: Instant) -> bool {
+        if !self.focus_follows_mouse {
+            return false;
+        }
+        if let Some((hovered_hwnd, _)) = self.hovered {
+            if hovered_hwnd == hwnd {
+                return false;
+            }
+        }
+        self.hovered = Some((hwnd, now));
+        return true;
+    }
+
+    /*
+       Decide if the hovered window should receive focus. Only managed windows are focused,
+       and only once the cursor has rested on them for at least the configured delay
+    */
+    pub fn focus_candidate(
+        &mut self,
+        now: Instant,
+        focused: HWND,
+        managed_hwnds: &[HWND],
+    ) -> Option<HWND> {
+        if !self.focus_follows_mouse {
+            return None;
+        }
+        let (hwnd, since) = self.hovered?;
+        if now.duration_since(since) < self.delay {
+            return None;
+        }
+        if hwnd == focused || !managed_hwnds.contains(&hwnd) {
+            return None;
+        }
+        return Some(hwnd);
+    }
+
+    // Only tiled windows can be dragged, returns false when the window under the cursor isn't one
+    pub fn start_drag(
+        &mut self,
+        kind: DragKind,
+        hwnd: HWND,
+        point: POINT,
+        tiled_hwnds: &[HWND],
+    ) -> bool {
+        if !tiled_hwnds.contains(&hwnd) {
+            return false;
+        }
+        self.drag = Some(Drag {
+            kind,
+            hwnd,
+            last_point: point,
+        });
+        return true;
+    }
+
+    // The window to resize and the pixels the cursor moved since the last update, for resize drags only
+    pub fn drag_to(&mut self, point: POINT, tiled_hwnds: &[HWND]) -> Option<(HWND, (i32, i32))> {
+        let drag = self.drag.as_mut()?;
+        if drag.kind != DragKind::RESIZE || !tiled_hwnds.contains(&drag.hwnd) {
+            return None;
+        }
+        let pixels = (point.x - drag.last_point.x, point.y - drag.last_point.y);
+        drag.last_point = point;
+        return Some((drag.hwnd, pixels));
+    }
+
+    // Ends the drag, a moved window trades places with the other tiled window it's dropped on
+    pub fn end_drag(&mut self, target: HWND, tiled_hwnds: &[HWND]) -> Option<(HWND, HWND)> {
+        let drag = self.drag.take()?;
+        if drag.kind != DragKind::MOVE
+            || target == drag.hwnd
+            || !tiled_hwnds.contains(&target)
+            || !tiled_hwnds.contains(&drag.hwnd)
+        {
+            return None;
+        }
+        return Some((drag.hwnd, target));
+    }
+
+    // The point the cursor should be warped to after focusing the given rect, if enabled
+    pub fn warp_target(&self, rect: &RECT) -> Option<POINT> {
+        if !self.mouse_follows_focus {
+            return None;
+        }
+        return Some(center(rect));
+    }
+}
+
+pub fn center(rect: &RECT) -> POINT {
+    POINT {
+        x: rect.left + (rect.right - rect.left) / 2,
+        y: rect.top + (rect.bottom - rect.top) / 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use windows::Win32::Foundation::{HWND, POINT, RECT};
+
+    use crate::data::config::Settings;
+    use crate::data::drag::DragKind;
+    use crate::state::management::mouse_manager::MouseManager;
+
+    const FIRST: HWND = HWND(1);
+    const SECOND: HWND = HWND(2);
+    const FLOATING: HWND = HWND(3);
+
+    fn manager(focus_follows_mouse: bool, mouse_follows_focus: bool) -> MouseManager {
+        let settings = Settings {
+            focus_follows_mouse,
+            mouse_follows_focus,
+            focus_follows_mouse_delay: 100,
+            ..Settings::default()
+        };
+        return MouseManager::new(&settings);
+    }
+
+    fn point(x: i32, y: i32) -> POINT {
+        return POINT { x, y };
+    }
+
+    #[test]
+    fn hover_focuses_managed_windows_after_the_delay() {
+        let mut mouse_manager = manager(true, false);
+        let start = Instant::now();
+        assert!(mouse_manager.hover(SECOND, start));
+        // Moving within the same window doesn't restart the delay
+        assert!(!mouse_manager.hover(SECOND, start + Duration::from_millis(50)));
+        assert_eq!(
+            mouse_manager.focus_candidate(
+                start + Duration::from_millis(99),
+                FIRST,
+                &[FIRST, SECOND]
+            ),
+            None
+        );
+        assert_eq!(
+            mouse_manager.focus_candidate(
+                start + Duration::from_millis(100),
+                FIRST,
+                &[FIRST, SECOND]
+            ),
+            Some(SECOND)
+        );
+    }
+
+    #[test]
+    fn hover_skips_focused_unmanaged_windows_and_disabled_setting() {
+        let later = Instant::now() + Duration::from_secs(1);
+        let mut mouse_manager = manager(true, false);
+        mouse_manager.hover(FIRST, Instant::now());
+        assert_eq!(mouse_manager.focus_candidate(later, FIRST, &[FIRST]), None);
+        mouse_manager.hover(FLOATING, Instant::now());
+        assert_eq!(mouse_manager.focus_candidate(later, FIRST, &[FIRST]), None);
+
+        let mut disabled = manager(false, false);
+        assert!(!disabled.hover(SECOND, Instant::now()));
+        assert_eq!(
+            disabled.focus_candidate(later, FIRST, &[FIRST, SECOND]),
+            None
+        );
+    }
+
+    #[test]
+    fn only_the_focus_delay_timer_is_handled() {
+        let mut mouse_manager = manager(true, false);
+        mouse_manager.timer = 7;
+        assert!(!mouse_manager.timer_fired(0));
+        assert!(!mouse_manager.timer_fired(8));
+        assert!(mouse_manager.timer_fired(7));
+        assert_eq!(mouse_manager.timer, 0);
+        assert!(!mouse_manager.timer_fired(7));
+    }
+
+    #[test]
+    fn cursor_warps_to_the_center_when_enabled() {
+        let rect = RECT {
+            left: 100,
+            top: 50,
+            right: 301,
+            bottom: 151,
+        };
+        let target = manager(false, true).warp_target(&rect).unwrap();
+        assert_eq!((target.x, target.y), (200, 100));
+        assert!(manager(false, false).warp_target(&rect).is_none());
+    }
+
+    #[test]
+    fn resize_drag_reports_the_movement_since_the_last_update() {
+        let tiled = [FIRST, SECOND];
+        let mut mouse_manager = manager(false, false);
+        assert_eq!(mouse_manager.drag_to(point(5, 5), &tiled), None);
+        assert!(!mouse_manager.start_drag(DragKind::RESIZE, FLOATING, point(0, 0), &tiled));
+        assert!(mouse_manager.drag.is_none());
+
+        assert!(mouse_manager.start_drag(DragKind::RESIZE, FIRST, point(10, 10), &tiled));
+        assert_eq!(
+            mouse_manager.drag_to(point(15, 8), &tiled),
+            Some((FIRST, (5, -2)))
+        );
+        assert_eq!(
+            mouse_manager.drag_to(point(20, 8), &tiled),
+            Some((FIRST, (5, 0)))
+        );
+        // The window stopped being tiled mid drag
+        assert_eq!(mouse_manager.drag_to(point(30, 8), &[SECOND]), None);
+        assert_eq!(mouse_manager.end_drag(SECOND, &tiled), None);
+        assert!(mouse_manager.drag.is_none());
+    }
+
+    #[test]
+    fn move_drag_swaps_with_the_tiled_window_it_is_dropped_on() {
+        let tiled = [FIRST, SECOND];
+        let mut mouse_manager = manager(false, false);
+        assert!(mouse_manager.start_drag(DragKind::MOVE, FIRST, point(0, 0), &tiled));
+        assert_eq!(mouse_manager.drag_to(point(50, 50), &tiled), None);
+        assert_eq!(
+            mouse_manager.end_drag(SECOND, &tiled),
+            Some((FIRST, SECOND))
+        );
+        assert_eq!(mouse_manager.end_drag(SECOND, &tiled), None);
+
+        for target in [FIRST, FLOATING] {
+            mouse_manager.start_drag(DragKind::MOVE, FIRST, point(0, 0), &tiled);
+            assert_eq!(mouse_manager.end_drag(target, &tiled), None);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use windows::Win32::Foundation::{HWND, RECT};
+
+use crate::state::init;
+use crate::win_api;
+use crate::win_api::overlay::OverlayContent;
+
+// Draws the tab strip overlays listing the windows of stacked groups
+pub struct StackManager {
+    enabled: bool,
+    active_color: u32,
+    inactive_color: u32,
+    overlays: HashMap<usize, HWND>,
+}
+
+impl StackManager {
+    pub fn new(enabled: bool, active_color: u32, inactive_color: u32) -> Self {
+        Self {
+            enabled,
+            active_color,
+            inactive_color,
+            overlays: HashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Takes effect on the next update, tab strips are destroyed right away when disabled
+    pub fn set_style(&mut self, enabled: bool, active_color: u32, inactive_color: u32) {
+        if !enabled {
+            self.retain_groups(&[]);
+        }
+        self.enabled = enabled;
+        self.active_color = active_color;
+        self.inactive_color = inactive_color;
+    }
+
+    pub fn show(&mut self, group: usize, rect: RECT, titles: Vec<String>, active: usize) {
+        let overlay = *self
+            .overlays
+            .entry(group)
+            .or_insert_with(init::overlay_window);
+        win_api::overlay::set_content(
+            overlay,
+            OverlayContent::Tabs {
+                titles,
+                active,
+                active_color: self.active_color,
+                inactive_color: self.inactive_color,
+            },
+        );
+        win_api::overlay::show(overlay, rect);
+    }
+
+    pub fn hide(&self, group: usize) {
+        if let Some(overlay) = self.overlays.get(&group) {
+            win_api::overlay::hide(*overlay);
+        }
+    }
+
+    // Destroy the tab strips of groups which are no longer stacked
+    pub fn retain_groups(&mut self, groups: &[usize]) {
+        self.overlays.retain(|group, overlay| {
+            if groups.contains(group) {
+                return true;
+            }
+            win_api::overlay::destroy(*overlay);
+            return false;
+        });
+    }
+}
@@ -1,20 +1,31 @@
+use crate::actions::exec::Exec;
+use crate::data::border::BorderStyle;
 use crate::data::common::axis::Axis;
 use crate::data::common::direction::Direction;
 use crate::data::common::direction::Direction::{DOWN, LEFT, RIGHT, UP};
 use crate::data::common::state::AppState;
-use crate::data::group::Group;
+use crate::data::config::{Config, Settings, MAX_WORKSPACES};
+use crate::data::drag::DragKind;
+use crate::data::group::{Group, Layout};
 use crate::data::hook::Hook;
-use crate::data::workspace::Workspace;
+use crate::data::window::{Window, WindowMetadata};
+use crate::data::workspace::{centered_rect, workspace_index, Workspace};
 use crate::state::init;
+use crate::state::management::border_manager::BorderManager;
 use crate::state::management::group_manager::GroupManager;
+use crate::state::management::keybindings_manager::KeybindingsManager;
+use crate::state::management::launch_manager::LaunchManager;
 use crate::state::management::monitor_manager::MonitorManager;
+use crate::state::management::mouse_manager::MouseManager;
+use crate::state::management::stack_manager::StackManager;
 use crate::state::management::window_manager::WindowManager;
 use crate::state::management::workspace_manager::WorkspaceManager;
-use crate::{state, win_api};
-use log::{debug, error, warn};
+use crate::{hooks, state, win_api};
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
 use std::process::exit;
-use windows::Win32::Foundation::{HWND, RECT};
+use std::time::Instant;
+use windows::Win32::Foundation::{HWND, POINT, RECT};
 use windows::Win32::Graphics::Gdi::HMONITOR;
 
 pub struct StateManager {
@@ -23,25 +34,34 @@ pub struct StateManager {
     pub group_manager: GroupManager,
     pub workspace_manager: WorkspaceManager,
     pub monitor_manager: MonitorManager,
+    pub mouse_manager: MouseManager,
+    pub border_manager: BorderManager,
+    pub stack_manager: StackManager,
+    pub keybindings_manager: KeybindingsManager,
+    launch_manager: LaunchManager,
+    pub settings: Settings,
+    binding_mode: Option<String>,
     pub ignore_events: bool,
 }
 
 impl StateManager {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
+        let settings = config.settings.clone();
         let mut monitors = state::init::monitors();
         let mut workspaces: Vec<Workspace> = Vec::new();
         let mut groups: Vec<Group> = Vec::new();
-        let windows = win_api::window::get_all();
-        let mut monitor_index = 0;
-        monitors.iter_mut().for_each(|monitor| unsafe {
-            let mon_left = monitor.device_mode.Anonymous1.Anonymous2.dmPosition.x;
-            let mon_top = monitor.device_mode.Anonymous1.Anonymous2.dmPosition.y;
-            let mon_width = monitor.device_mode.dmPelsWidth;
-            let mon_height = monitor.device_mode.dmPelsHeight;
-            let mon_right = mon_left + mon_width as i32;
-            let mut mon_bottom = mon_top + mon_height as i32;
-            let taskbar_offset = monitor.info.rcMonitor.bottom - monitor.info.rcWork.bottom;
-            mon_bottom -= taskbar_offset;
+        let window_manager =
+            WindowManager::new(win_api::window::get_all(), settings.ignore_rules.clone());
+        // Windows floated by a rule never join a group, the rules are applied once the managers exist
+        let windows: Vec<Window> = window_manager
+            .windows()
+            .iter()
+            .filter(|window| !settings.floats_by_rule(&window.title))
+            .cloned()
+            .collect();
+        monitors.iter_mut().for_each(|monitor| {
+            let layout = settings.monitor_layout(&monitor.name);
+            let (area, split_axis) = monitor.layout_area(&layout);
             let mut windows_on_monitor = Vec::new();
             windows.iter().for_each(|window| {
                 let hmonitor = win_api::monitor::hmonitor_from_hwnd(window.hwnd);
@@ -49,8 +69,7 @@ impl StateManager {
                     windows_on_monitor.push(window);
                 }
             });
-            let is_landscape = mon_width > mon_height;
-            if is_landscape {
+            if monitor.is_landscape() {
                 windows_on_monitor.sort_by(|window, other_window| {
                     window
                         .rect
@@ -63,24 +82,19 @@ impl StateManager {
                     window.rect.top.partial_cmp(&other_window.rect.top).unwrap()
                 });
             }
-            let adjusted_index = monitor_index * 10;
+            // Workspaces and groups are looked up by their position, so they are numbered in order
+            let adjusted_index = workspaces.len();
             // Create default group and workspace
             groups.push(Group {
                 index: adjusted_index,
-                split_axis: match is_landscape {
-                    true => Axis::VERTICAL,
-                    false => Axis::HORIZONTAL,
-                },
-                rect: RECT {
-                    left: mon_left,
-                    top: mon_top,
-                    right: mon_right,
-                    bottom: mon_bottom,
-                },
+                split_axis,
+                layout: Layout::SPLIT,
+                rect: area,
                 windows: windows_on_monitor
                     .into_iter()
                     .map(|window| window.hwnd)
                     .collect(),
+                weights: HashMap::new(),
             });
             workspaces.push(Workspace {
                 index: adjusted_index,
@@ -89,20 +103,14 @@ impl StateManager {
             });
             monitor.workspaces.push(adjusted_index);
             // Create empty groups and workspaces
-            for i in 1..10 {
+            for i in 1..layout.workspace_count.unwrap_or(MAX_WORKSPACES) {
                 let group = Group {
                     index: adjusted_index + i,
-                    split_axis: match is_landscape {
-                        true => Axis::VERTICAL,
-                        false => Axis::HORIZONTAL,
-                    },
-                    rect: RECT {
-                        left: mon_left,
-                        top: mon_top,
-                        right: mon_right,
-                        bottom: mon_bottom,
-                    },
+                    split_axis,
+                    layout: Layout::SPLIT,
+                    rect: area,
                     windows: vec![],
+                    weights: HashMap::new(),
                 };
                 let workspace = Workspace {
                     index: adjusted_index + i,
@@ -113,20 +121,49 @@ impl StateManager {
                 workspaces.push(workspace);
                 monitor.workspaces.push(adjusted_index + i);
             }
-            monitor_index += 1;
         });
         let monitor_manager = MonitorManager::new(monitors);
         let workspace_manager = WorkspaceManager::new(workspaces);
-        let group_manager = GroupManager::new(groups);
-        let window_manager = WindowManager::new(windows);
-        Self {
-            state: init::application(),
+        let tab_height = match settings.stack_tabs {
+            true => settings.stack_tab_height as i32,
+            false => 0,
+        };
+        let group_manager = GroupManager::new(groups, tab_height);
+        let mouse_manager = MouseManager::new(&settings);
+        let border_style = BorderStyle::from(&settings);
+        let border_overlay = match border_style.enabled() {
+            true => init::overlay_window(),
+            false => HWND::default(),
+        };
+        let border_manager = BorderManager::new(border_overlay, border_style);
+        let stack_manager = StackManager::new(
+            settings.stack_tabs,
+            settings.border_active_color,
+            settings.border_inactive_color,
+        );
+        let mut state_manager = Self {
+            state: init::application(&settings),
             window_manager,
             group_manager,
             workspace_manager,
             monitor_manager,
+            mouse_manager,
+            border_manager,
+            stack_manager,
+            keybindings_manager: KeybindingsManager::new(),
+            launch_manager: LaunchManager::new(),
+            settings,
+            binding_mode: None,
             ignore_events: false,
-        }
+        };
+        state_manager
+            .window_manager
+            .managed_hwnds(false)
+            .into_iter()
+            .for_each(|hwnd| {
+                state_manager.apply_window_rules(hwnd);
+            });
+        return state_manager;
     }
 
     pub fn arrange_all_windows(&mut self) {
@@ -141,6 +178,60 @@ impl StateManager {
         &mut self.state.hooks
     }
 
+    /*
+       Swap in the settings of a reloaded config. The managers pick up their new values in place,
+       so windows, groups and workspaces are left as they are
+    */
+    pub fn apply_settings(&mut self, settings: Settings) {
+        let needs_mouse_hook =
+            |settings: &Settings| settings.focus_follows_mouse || settings.mouse_bindings;
+        if needs_mouse_hook(&settings) != needs_mouse_hook(&self.settings) {
+            hooks::unset_hooks(&mut self.state.hooks);
+            self.state.hooks = hooks::set_hooks(&settings);
+        }
+        let tab_height = match settings.stack_tabs {
+            true => settings.stack_tab_height as i32,
+            false => 0,
+        };
+        self.group_manager.set_tab_height(tab_height);
+        self.apply_layout(&settings);
+        self.mouse_manager.apply_settings(&settings);
+        self.window_manager
+            .set_ignore_rules(settings.ignore_rules.clone());
+        self.border_manager.set_style(BorderStyle::from(&settings));
+        self.stack_manager.set_style(
+            settings.stack_tabs,
+            settings.border_active_color,
+            settings.border_inactive_color,
+        );
+        self.settings = settings;
+        self.arrange_all_windows();
+        self.update_overlays();
+    }
+
+    /*
+       Move the groups of every monitor into the area its split_axis and taskbar_offset settings
+       describe. Workspaces are created on startup, so a changed workspace_count waits for a restart
+    */
+    fn apply_layout(&mut self, settings: &Settings) {
+        for monitor in self.monitor_manager.monitors() {
+            let layout = settings.monitor_layout(&monitor.name);
+            if layout.workspace_count != self.settings.monitor_layout(&monitor.name).workspace_count
+            {
+                warn!(
+                    "Changing workspace_count of {} takes effect once Fenestra is restarted",
+                    monitor.name
+                );
+            }
+            let (area, split_axis) = monitor.layout_area(&layout);
+            for workspace in monitor.workspaces.iter() {
+                for group in self.workspace_manager.groups_for_workspace(*workspace) {
+                    self.group_manager.set_area(group, area, split_axis);
+                }
+            }
+        }
+    }
+
     pub fn current_monitor(&self) -> HMONITOR {
         self.monitor_manager.get_current()
     }
@@ -186,10 +277,76 @@ impl StateManager {
             let workspace = self.workspace_manager.workspace_for_group(group);
             let groups_on_workspace = self.workspace_manager.groups_for_workspace(workspace);
             self.window_manager.set_positions(&group_positions);
-            if group_positions.len() == 1 && groups_on_workspace.len() == 1 {
+            if group_positions.len() == 1
+                && groups_on_workspace.len() == 1
+                && !self.group_manager.is_stacked(group)
+            {
                 self.window_manager.maximize(&group_positions[0].0);
             }
         }
+        self.update_overlays();
+    }
+
+    pub fn update_overlays(&mut self) {
+        self.update_border();
+        self.update_tabs();
+    }
+
+    // Draw a tab strip above every visible stacked group, highlighting the focused window
+    pub fn update_tabs(&mut self) {
+        if !self.stack_manager.enabled() {
+            return;
+        }
+        let foreground_hwnd = win_api::window::foreground_hwnd();
+        let managed_hwnds = self.window_manager.managed_hwnds(true);
+        let stacked_groups = self.group_manager.stacked_groups();
+        self.stack_manager.retain_groups(&stacked_groups);
+        for group in stacked_groups {
+            let workspace = self.workspace_manager.workspace_for_group(group);
+            let hwnds: Vec<HWND> = self
+                .group_manager
+                .hwnds_from_groups(vec![group])
+                .into_iter()
+                .filter(|hwnd| managed_hwnds.contains(hwnd))
+                .collect();
+            if !self.workspace_manager.is_active(workspace) || hwnds.is_empty() {
+                self.stack_manager.hide(group);
+                continue;
+            }
+            let titles = hwnds
+                .iter()
+                .map(|hwnd| self.window_manager.title(hwnd).unwrap_or_default())
+                .collect();
+            let active = hwnds
+                .iter()
+                .position(|hwnd| *hwnd == foreground_hwnd)
+                .unwrap_or(0);
+            let rect = self.group_manager.tab_strip_rect(group);
+            self.stack_manager.show(group, rect, titles, active);
+        }
+    }
+
+    // Move the border overlay onto the focused tiled window
+    pub fn update_border(&mut self) {
+        if !self.border_manager.enabled() {
+            return;
+        }
+        let foreground_hwnd = win_api::window::foreground_hwnd();
+        let managed_hwnds = self.window_manager.managed_hwnds(true);
+        let target = self
+            .border_manager
+            .target(foreground_hwnd, &managed_hwnds)
+            .and_then(|(hwnd, active)| {
+                self.window_manager
+                    .rects(&hwnd)
+                    .map(|(rect, shadow_rect, dpi)| (rect, shadow_rect, dpi, active))
+            });
+        match target {
+            Some((rect, shadow_rect, dpi, active)) => {
+                self.border_manager.show(&rect, &shadow_rect, dpi, active)
+            }
+            None => self.border_manager.hide(),
+        }
     }
 
     pub fn add_window(&mut self, hwnd: HWND) {
@@ -199,22 +356,223 @@ impl StateManager {
         }
         let window_title = win_api::window::get_window_title(hwnd);
         debug!("Added window '{}' ({})", window_title, hwnd.0);
+        if self.apply_window_rules(hwnd) || self.route_launched_window(hwnd) {
+            return;
+        }
         let group = self.current_group();
         let new_positions = self.group_manager.add_window(group, hwnd);
         self.arrange_windows(new_positions);
     }
 
+    // The first window of a program started by exec with a workspace opens on that workspace
+    fn route_launched_window(&mut self, hwnd: HWND) -> bool {
+        if self.group_manager.managed_hwnds().contains(&&hwnd) {
+            return false;
+        }
+        let process_id = match self.window_manager.process_id(&hwnd) {
+            Some(process_id) => process_id,
+            None => return false,
+        };
+        let workspace = match self.launch_manager.claim(process_id, Instant::now()) {
+            Some(workspace) => workspace,
+            None => return false,
+        };
+        debug!("Routing launched window to workspace {}", workspace);
+        let group = self.workspace_manager.groups_for_workspace(workspace)[0];
+        if self.workspace_manager.is_active(workspace) {
+            let new_positions = self.group_manager.add_window(group, hwnd);
+            self.arrange_windows(new_positions);
+        } else {
+            self.ignore_events = true;
+            self.window_manager.minimize(&hwnd);
+            self.group_manager.add_window(group, hwnd);
+            self.ignore_events = false;
+        }
+        return true;
+    }
+
     pub fn remove_window(&mut self, hwnd: HWND) {
+        if self.window_manager.is_floating(&hwnd) {
+            self.window_manager.remove_window(hwnd);
+            self.workspace_manager.set_sticky(hwnd, false);
+            self.workspace_manager.remove_from_scratchpad(&hwnd);
+            return;
+        }
         if !self.window_manager.managed_hwnds(true).contains(&hwnd) {
             return;
         }
         if self.window_manager.remove_window(hwnd) {
+            self.workspace_manager.set_sticky(hwnd, false);
             let new_positions = self.group_manager.remove_window(&hwnd);
             debug!("Removed '{}'", win_api::window::get_window_title(hwnd));
             self.arrange_windows(new_positions);
         }
     }
 
+    pub fn is_tiled(&self, hwnd: &HWND) -> bool {
+        self.group_manager.managed_hwnds().contains(&hwnd)
+    }
+
+    fn tiled_hwnds(&self) -> Vec<HWND> {
+        return self
+            .group_manager
+            .managed_hwnds()
+            .into_iter()
+            .copied()
+            .collect();
+    }
+
+    /*
+       Apply the configured scratchpad_window and sticky_window rules matching the title of a window
+       which isn't tiled yet. Returns true when the window floats, so it must not join a group
+    */
+    fn apply_window_rules(&mut self, hwnd: HWND) -> bool {
+        if self.workspace_manager.is_sticky(&hwnd) || self.workspace_manager.in_scratchpad(&hwnd) {
+            return self.window_manager.is_floating(&hwnd);
+        }
+        let title = match self.window_manager.title(&hwnd) {
+            Some(title) => title,
+            None => return false,
+        };
+        if let Some(name) = self.settings.scratchpad_rule(&title) {
+            self.window_manager.set_floating(hwnd, true);
+            self.store_in_scratchpad(hwnd, Some(name));
+            return true;
+        }
+        if !self.settings.sticky_rule(&title) {
+            return false;
+        }
+        debug!("Setting sticky=true for '{}'", title);
+        self.workspace_manager.set_sticky(hwnd, true);
+        if self.settings.sticky_tiled {
+            return false;
+        }
+        self.window_manager.set_floating(hwnd, true);
+        return true;
+    }
+
+    /*
+       Sticky windows float above the tiled layout by default, so they are never minimized when
+       switching workspaces. With sticky_tiled they stay in the layout and follow the active workspace
+    */
+    pub fn set_sticky(&mut self, hwnd: HWND, sticky: bool) {
+        debug!(
+            "Setting sticky={} for '{}'",
+            sticky,
+            win_api::window::get_window_title(hwnd)
+        );
+        self.workspace_manager.set_sticky(hwnd, sticky);
+        if self.settings.sticky_tiled {
+            return;
+        }
+        if sticky {
+            let new_positions = self.group_manager.remove_window(&hwnd);
+            self.window_manager.set_floating(hwnd, true);
+            self.arrange_windows(new_positions);
+        } else {
+            self.window_manager.set_floating(hwnd, false);
+            let group = self.current_group();
+            let new_positions = self.group_manager.add_window(group, hwnd);
+            self.arrange_windows(new_positions);
+        }
+    }
+
+    // Focus a window, warping the cursor to it when mouse_follows_focus is enabled
+    pub fn focus(&mut self, hwnd: HWND) {
+        self.window_manager.focus(hwnd);
+        if let Some(rect) = self.window_manager.rect(&hwnd) {
+            self.warp_cursor(&rect);
+        }
+    }
+
+    pub fn warp_cursor(&self, rect: &RECT) {
+        if let Some(point) = self.mouse_manager.warp_target(rect) {
+            win_api::window::set_cursor_position(point);
+        }
+    }
+
+    pub fn handle_mouse_move(&mut self, point: POINT) {
+        if self.mouse_manager.drag.is_some() {
+            let tiled_hwnds = self.tiled_hwnds();
+            if let Some((hwnd, pixels)) = self.mouse_manager.drag_to(point, &tiled_hwnds) {
+                self.resize_dragged(hwnd, pixels);
+            }
+            return;
+        }
+        let hwnd = win_api::window::hwnd_from_point(point);
+        if self.mouse_manager.hover(hwnd, Instant::now()) {
+            let delay = self.mouse_manager.delay().as_millis() as u32;
+            self.mouse_manager.timer = win_api::window::set_timer(self.mouse_manager.timer, delay);
+        }
+    }
+
+    // Start dragging the tiled window under the cursor, the drag lasts until the mouse button is released
+    pub fn start_drag(&mut self, kind: DragKind) {
+        let point = win_api::window::get_cursor_position();
+        let hwnd = win_api::window::hwnd_from_point(point);
+        let tiled_hwnds = self.tiled_hwnds();
+        if self
+            .mouse_manager
+            .start_drag(kind, hwnd, point, &tiled_hwnds)
+        {
+            debug!(
+                "Dragging '{}' ({:?})",
+                win_api::window::get_window_title(hwnd),
+                kind
+            );
+        }
+    }
+
+    fn resize_dragged(&mut self, hwnd: HWND, pixels: (i32, i32)) {
+        let manageable_windows = self.window_manager.managed_hwnds(true);
+        let group = self
+            .group_manager
+            .move_window_edge(&hwnd, pixels, &manageable_windows);
+        let new_positions = self
+            .group_manager
+            .calculate_window_positions(vec![group], &manageable_windows);
+        self.arrange_windows(new_positions);
+    }
+
+    // A moved window trades places with the tiled window it's dropped on
+    pub fn end_drag(&mut self) {
+        if self.mouse_manager.drag.is_none() {
+            return;
+        }
+        let target = win_api::window::hwnd_from_point(win_api::window::get_cursor_position());
+        let tiled_hwnds = self.tiled_hwnds();
+        let (hwnd, target) = match self.mouse_manager.end_drag(target, &tiled_hwnds) {
+            Some(swap) => swap,
+            None => return,
+        };
+        let updated_groups = self.group_manager.swap_windows(hwnd, target);
+        // The windows may trade monitors as well
+        self.window_manager.update_dpi(hwnd);
+        self.window_manager.update_dpi(target);
+        let manageable_windows = self.window_manager.managed_hwnds(true);
+        self.arrange_windows(
+            self.group_manager
+                .calculate_window_positions(updated_groups, &manageable_windows),
+        );
+        self.update_overlays();
+    }
+
+    pub fn handle_timer(&mut self, timer_id: usize) {
+        if !self.mouse_manager.timer_fired(timer_id) {
+            return;
+        }
+        win_api::window::kill_timer(timer_id);
+        let focused_hwnd = win_api::window::foreground_hwnd();
+        let managed_hwnds = self.window_manager.managed_hwnds(true);
+        let candidate =
+            self.mouse_manager
+                .focus_candidate(Instant::now(), focused_hwnd, &managed_hwnds);
+        if let Some(hwnd) = candidate {
+            // Focus without warping, the cursor is already over the window
+            self.window_manager.focus(hwnd);
+        }
+    }
+
     pub fn validate(&mut self) {
         // Ensure that every managed window has a group
         let num_windows = self.window_manager.managed_hwnds(false).len();
@@ -224,6 +582,8 @@ impl StateManager {
             exit(100);
         }
         let (removed, added) = self.window_manager.validate_windows();
+        let tracked_hwnds = self.window_manager.tracked_hwnds();
+        self.workspace_manager.retain_windows(&tracked_hwnds);
         removed.iter().for_each(|hwnd| {
             self.group_manager.remove_window(&hwnd);
         });
@@ -268,7 +628,7 @@ impl StateManager {
                 self.window_manager.managed_hwnds(true),
             );
             if nearest_hwnd_opt.is_some() {
-                self.window_manager.focus(nearest_hwnd_opt.unwrap());
+                self.focus(nearest_hwnd_opt.unwrap());
                 return;
             }
         }
@@ -285,7 +645,7 @@ impl StateManager {
                     &direction,
                     self.window_manager.managed_hwnds(true),
                 );
-                self.window_manager.focus(hwnd);
+                self.focus(hwnd);
                 return;
             }
         }
@@ -309,7 +669,7 @@ impl StateManager {
                     &direction,
                     self.window_manager.managed_hwnds(true),
                 );
-                self.window_manager.focus(hwnd);
+                self.focus(hwnd);
                 return;
             }
         }
@@ -410,7 +770,13 @@ impl StateManager {
         let workspaces = self
             .monitor_manager
             .workspaces_for_monitor(current_hmonitor);
-        let target_workspace = workspaces[workspace_index];
+        let target_workspace = match workspaces.get(workspace_index) {
+            Some(workspace) => *workspace,
+            None => {
+                debug!("Monitor has no workspace {}", workspace_index + 1);
+                return;
+            }
+        };
         if current_workspace == target_workspace {
             debug!(
                 "Ignoring request to focus current workspace {}",
@@ -425,8 +791,15 @@ impl StateManager {
         let requested_groups = self
             .workspace_manager
             .groups_for_workspace(target_workspace);
-        let visible_hwnds = self.group_manager.hwnds_from_groups(visible_groups);
+        let mut visible_hwnds = self.group_manager.hwnds_from_groups(visible_groups);
         let requested_hwnds = self.group_manager.hwnds_from_groups(requested_groups);
+        // Tiled sticky windows stay visible and move into the requested workspace's layout
+        let sticky_hwnds: Vec<HWND> = visible_hwnds
+            .iter()
+            .filter(|hwnd| self.workspace_manager.is_sticky(hwnd))
+            .cloned()
+            .collect();
+        visible_hwnds.retain(|hwnd| !sticky_hwnds.contains(hwnd));
         self.ignore_events = true;
         visible_hwnds
             .iter()
@@ -436,7 +809,43 @@ impl StateManager {
             .iter()
             .for_each(|hwnd| self.window_manager.restore(&hwnd));
         self.workspace_manager.toggle_active(target_workspace);
+        if !sticky_hwnds.is_empty() {
+            let target_group = self
+                .workspace_manager
+                .groups_for_workspace(target_workspace)[0];
+            let mut new_positions = Vec::new();
+            for hwnd in sticky_hwnds {
+                new_positions.extend(self.group_manager.remove_window(&hwnd));
+                new_positions.extend(self.group_manager.add_window(target_group, hwnd));
+            }
+            self.arrange_windows(new_positions);
+        }
         self.ignore_events = false;
+        // The last restored window ends up in the foreground
+        let focused_rect = match requested_hwnds.last() {
+            Some(hwnd) => self.window_manager.rect(hwnd),
+            None => None,
+        };
+        let rect = focused_rect.unwrap_or(self.monitor_manager.work_area(current_hmonitor));
+        self.warp_cursor(&rect);
+        self.update_overlays();
+    }
+
+    // Focus the workspace a number of steps after (or before) the current one, wrapping around
+    pub fn cycle_workspace(&mut self, step: i32) {
+        let current_workspace = self.current_workspace();
+        let workspaces = self
+            .monitor_manager
+            .workspaces_for_monitor(self.current_monitor());
+        let current_index = match workspaces
+            .iter()
+            .position(|workspace| *workspace == current_workspace)
+        {
+            Some(index) => index as i32,
+            None => return,
+        };
+        let target_index = (current_index + step).rem_euclid(workspaces.len() as i32);
+        self.focus_workspace(target_index as usize);
     }
 
     pub fn move_to_workspace(&mut self, workspace_index: usize) {
@@ -444,7 +853,13 @@ impl StateManager {
         let workspaces = self
             .monitor_manager
             .workspaces_for_monitor(current_hmonitor);
-        let target_workspace = workspaces[workspace_index];
+        let target_workspace = match workspaces.get(workspace_index) {
+            Some(workspace) => *workspace,
+            None => {
+                debug!("Monitor has no workspace {}", workspace_index + 1);
+                return;
+            }
+        };
         if self.current_workspace() == target_workspace {
             debug!(
                 "Ignoring request to send monitor to current workspace {}",
@@ -453,6 +868,10 @@ impl StateManager {
             return;
         }
         let hwnd = win_api::window::foreground_hwnd();
+        if self.workspace_manager.is_sticky(&hwnd) {
+            debug!("Ignoring request to send sticky window to another workspace");
+            return;
+        }
         debug!(
             "Moving '{}' to workspace {}",
             win_api::window::get_window_title(hwnd),
@@ -469,7 +888,7 @@ impl StateManager {
                 .filter(|h| h.0 != hwnd.0)
                 .last()
                 .unwrap();
-            self.window_manager.focus(*target_hwnd);
+            self.focus(*target_hwnd);
         }
         let new_positions = self.group_manager.remove_window(&hwnd);
         self.arrange_windows(new_positions);
@@ -481,6 +900,168 @@ impl StateManager {
         self.ignore_events = false;
     }
 
+    // Workspace numbers refer to the workspaces of the current monitor, like focus_workspace
+    pub fn exec(&mut self, exec: &Exec) {
+        let workspace = exec.workspace.and_then(|id| {
+            let workspaces = self
+                .monitor_manager
+                .workspaces_for_monitor(self.current_monitor());
+            let workspace = workspaces.get(workspace_index(id)).cloned();
+            if workspace.is_none() {
+                warn!(
+                    "Workspace {} doesn't exist, starting on the current one",
+                    id
+                );
+            }
+            workspace
+        });
+        self.launch_manager.spawn(exec, workspace);
+    }
+
+    pub fn toggle_sticky(&mut self) {
+        let hwnd = win_api::window::foreground_hwnd();
+        let sticky = !self.workspace_manager.is_sticky(&hwnd);
+        if sticky && !self.window_manager.managed_hwnds(true).contains(&hwnd) {
+            return;
+        }
+        self.set_sticky(hwnd, sticky);
+    }
+
+    pub fn move_to_scratchpad(&mut self, name: Option<String>) {
+        let hwnd = win_api::window::foreground_hwnd();
+        self.store_in_scratchpad(hwnd, name);
+    }
+
+    // Hide a window in the scratchpad, taking it out of the tiled layout
+    fn store_in_scratchpad(&mut self, hwnd: HWND, name: Option<String>) {
+        let tiled = self.is_tiled(&hwnd);
+        if !tiled && !self.window_manager.is_floating(&hwnd) {
+            return;
+        }
+        debug!(
+            "Moving '{}' to the scratchpad",
+            win_api::window::get_window_title(hwnd)
+        );
+        self.workspace_manager.set_sticky(hwnd, false);
+        self.ignore_events = true;
+        if tiled {
+            let new_positions = self.group_manager.remove_window(&hwnd);
+            self.window_manager.set_floating(hwnd, true);
+            self.arrange_windows(new_positions);
+        }
+        self.workspace_manager.add_to_scratchpad(hwnd, name);
+        self.window_manager.minimize(&hwnd);
+        self.ignore_events = false;
+    }
+
+    // Show a scratchpad window centered over the current workspace, or hide it again
+    pub fn toggle_scratchpad(&mut self, name: &Option<String>) {
+        let (hwnd, visible) = match self.workspace_manager.scratchpad_target(name) {
+            Some(target) => target,
+            None => {
+                debug!("No scratchpad window matches {:?}", name);
+                return;
+            }
+        };
+        self.ignore_events = true;
+        if visible {
+            self.window_manager.minimize(&hwnd);
+            self.workspace_manager.set_scratchpad_visible(&hwnd, false);
+        } else {
+            // Only one scratchpad window is shown at a time
+            for shown_hwnd in self.workspace_manager.visible_scratchpad_hwnds() {
+                self.window_manager.minimize(&shown_hwnd);
+                self.workspace_manager
+                    .set_scratchpad_visible(&shown_hwnd, false);
+            }
+            let work_area = self.monitor_manager.work_area(self.current_monitor());
+            let position = centered_rect(
+                &work_area,
+                self.settings.scratchpad_width,
+                self.settings.scratchpad_height,
+            );
+            self.window_manager.restore(&hwnd);
+            self.window_manager.set_position(hwnd, position, 0);
+            self.workspace_manager.set_scratchpad_visible(&hwnd, true);
+            self.focus(hwnd);
+        }
+        self.ignore_events = false;
+        self.update_overlays();
+    }
+
+    pub fn toggle_group_stacking(&mut self) {
+        let group = self.current_group();
+        let layout = self.group_manager.toggle_stacking(group);
+        debug!("Set group {} layout to {}", group, layout);
+        let manageable_windows = self.window_manager.managed_hwnds(true);
+        let new_positions = self
+            .group_manager
+            .calculate_window_positions(vec![group], &manageable_windows);
+        self.arrange_windows(new_positions);
+        // Keep the focused window on top of the stack
+        let hwnd = win_api::window::foreground_hwnd();
+        if self.is_tiled(&hwnd) {
+            self.focus(hwnd);
+        }
+    }
+
+    pub fn focus_next_in_stack(&mut self) {
+        let hwnd = win_api::window::foreground_hwnd();
+        if !self.is_tiled(&hwnd) {
+            return;
+        }
+        let managed_hwnds = self.window_manager.managed_hwnds(true);
+        if let Some(next_hwnd) = self.group_manager.next_in_group(&hwnd, &managed_hwnds) {
+            self.focus(next_hwnd);
+            self.update_tabs();
+        }
+    }
+
+    pub fn resize_window(&mut self, delta: f32) {
+        let hwnd = win_api::window::foreground_hwnd();
+        if !self.is_tiled(&hwnd) {
+            return;
+        }
+        let group = self.group_manager.resize_window(&hwnd, delta);
+        let manageable_windows = self.window_manager.managed_hwnds(true);
+        let new_positions = self
+            .group_manager
+            .calculate_window_positions(vec![group], &manageable_windows);
+        self.arrange_windows(new_positions);
+    }
+
+    // The active binding mode, None while the global keybinds apply
+    pub fn current_mode(&self) -> Option<&str> {
+        self.binding_mode.as_deref()
+    }
+
+    // Metadata of the foreground window, queried directly when it isn't managed
+    pub fn foreground_metadata(&self) -> WindowMetadata {
+        let hwnd = win_api::window::foreground_hwnd();
+        if hwnd.0 == 0 {
+            return WindowMetadata::default();
+        }
+        return self
+            .window_manager
+            .metadata(&hwnd)
+            .unwrap_or_else(|| win_api::window::get_window_metadata(hwnd));
+    }
+
+    pub fn enter_mode(&mut self, name: &str) {
+        info!("Entering binding mode {}", name);
+        self.binding_mode = Some(name.to_string());
+        hooks::keyboard::set_mode_active(true);
+        win_api::window::set_tray_mode(Some(name));
+    }
+
+    pub fn exit_mode(&mut self) {
+        if let Some(name) = self.binding_mode.take() {
+            info!("Leaving binding mode {}", name);
+        }
+        hooks::keyboard::set_mode_active(false);
+        win_api::window::set_tray_mode(None);
+    }
+
     pub fn set_split_axis(&mut self, axis: Axis) {
         let group = self.current_group();
         if self.group_manager.group_is_axis(group, &axis) {
@@ -1,4 +1,4 @@
-use crate::data::window::Window;
+use crate::data::window::{Window, WindowMetadata, WindowRule};
 use crate::win_api;
 use log::{debug, warn};
 use windows::Win32::Foundation::{HWND, RECT};
@@ -6,11 +6,34 @@ use windows::Win32::UI::WindowsAndMessaging::{WS_MAXIMIZE, WS_MINIMIZE};
 
 pub struct WindowManager {
     windows: Vec<Window>,
+    // Windows that are tracked, but excluded from tiling
+    floating: Vec<HWND>,
+    // Windows matching these are never tracked
+    ignore_rules: Vec<WindowRule>,
 }
 
 impl WindowManager {
-    pub fn new(windows: Vec<Window>) -> Self {
-        Self { windows }
+    pub fn new(windows: Vec<Window>, ignore_rules: Vec<WindowRule>) -> Self {
+        let mut window_manager = Self {
+            windows: Vec::new(),
+            floating: Vec::new(),
+            ignore_rules,
+        };
+        window_manager.windows = windows
+            .into_iter()
+            .filter(|window| !window_manager.is_ignored(window))
+            .collect();
+        return window_manager;
+    }
+
+    // Takes effect for windows opened from now on
+    pub fn set_ignore_rules(&mut self, ignore_rules: Vec<WindowRule>) {
+        self.ignore_rules = ignore_rules;
+    }
+
+    pub fn is_ignored(&self, window: &Window) -> bool {
+        let metadata = window.metadata();
+        return self.ignore_rules.iter().any(|rule| rule.matches(&metadata));
     }
 
     pub fn managed_hwnds(&self, exclude_minimized: bool) -> Vec<HWND> {
@@ -18,11 +41,65 @@ impl WindowManager {
             return self
                 .windows
                 .iter()
+                .filter(|window| !self.floating.contains(&window.hwnd))
                 .filter(|window| window.style as u32 & WS_MINIMIZE.0 == 0)
                 .map(|window| window.hwnd)
                 .collect();
         }
-        return self.windows.iter().map(|window| window.hwnd).collect();
+        return self
+            .windows
+            .iter()
+            .filter(|window| !self.floating.contains(&window.hwnd))
+            .map(|window| window.hwnd)
+            .collect();
+    }
+
+    pub fn windows(&self) -> &[Window] {
+        &self.windows
+    }
+
+    // Every known window, including floating ones
+    pub fn tracked_hwnds(&self) -> Vec<HWND> {
+        self.windows.iter().map(|window| window.hwnd).collect()
+    }
+
+    pub fn is_floating(&self, hwnd: &HWND) -> bool {
+        self.floating.contains(hwnd)
+    }
+
+    pub fn set_floating(&mut self, hwnd: HWND, floating: bool) {
+        self.floating.retain(|h| *h != hwnd);
+        if floating {
+            self.floating.push(hwnd);
+        }
+    }
+
+    pub fn title(&self, hwnd: &HWND) -> Option<String> {
+        self.windows
+            .iter()
+            .find(|window| window.hwnd == *hwnd)
+            .map(|window| window.title.clone())
+    }
+
+    // Read the title again after the window changed it
+    pub fn update_title(&mut self, hwnd: &HWND) {
+        if let Some(window) = self.windows.iter_mut().find(|window| window.hwnd == *hwnd) {
+            window.title = win_api::window::get_window_title(*hwnd);
+        }
+    }
+
+    pub fn process_id(&self, hwnd: &HWND) -> Option<u32> {
+        self.windows
+            .iter()
+            .find(|window| window.hwnd == *hwnd)
+            .map(|window| window.process_id)
+    }
+
+    pub fn metadata(&self, hwnd: &HWND) -> Option<WindowMetadata> {
+        self.windows
+            .iter()
+            .find(|window| window.hwnd == *hwnd)
+            .map(|window| window.metadata())
     }
 
     pub fn add_window(&mut self, hwnd: HWND) -> bool {
@@ -32,6 +109,9 @@ impl WindowManager {
             return false;
         }
         let window = window_result.unwrap();
+        if self.is_ignored(&window) && !self.windows.iter().any(|w| w.hwnd == window.hwnd) {
+            return false;
+        }
         if self.windows.iter().any(|w| w.hwnd == window.hwnd) {
             // Remove the outdated window state
             let old_len = self.windows.len();
@@ -47,6 +127,9 @@ impl WindowManager {
         // debug!("Adding window '{}'", window.title);
         let window_style = window.style;
         self.windows.push(window);
+        if self.floating.contains(&hwnd) {
+            return false;
+        }
         return window_style & WS_MINIMIZE.0 as i32 == 0;
     }
 
@@ -54,6 +137,7 @@ impl WindowManager {
         if !self.windows.iter().any(|window| window.hwnd == hwnd) {
             return false;
         }
+        self.floating.retain(|h| *h != hwnd);
         let old_len = self.windows.len();
         self.windows.retain(|w| w.hwnd != hwnd);
         let new_len = self.windows.len();
@@ -103,6 +187,21 @@ impl WindowManager {
         }
     }
 
+    pub fn rect(&self, hwnd: &HWND) -> Option<RECT> {
+        self.windows
+            .iter()
+            .find(|window| window.hwnd == *hwnd)
+            .map(|window| window.rect)
+    }
+
+    // The tiled rect, the DWM frame bounds and the DPI of the window
+    pub fn rects(&self, hwnd: &HWND) -> Option<(RECT, RECT, u32)> {
+        self.windows
+            .iter()
+            .find(|window| window.hwnd == *hwnd)
+            .map(|window| (window.rect, window.shadow_rect, window.dpi))
+    }
+
     pub fn close(&mut self, hwnd: HWND) {
         win_api::window::close(hwnd)
     }
@@ -141,6 +240,7 @@ impl WindowManager {
         position.bottom += window.border_thickness as i32;
         let dpi = window.dpi;
         win_api::window::set_position(&window.hwnd, position, current_dpi != dpi);
+        window.shadow_rect = win_api::window::get_frame_bounds(hwnd);
         debug!(
             "Set position for '{}': {{X: {}, Y: {}, width: {}, height: {}}}",
             window.title,
@@ -158,16 +258,23 @@ impl WindowManager {
             let hwnd = self.windows[i].hwnd;
             if win_api::window::get_window(hwnd).is_none() {
                 self.windows.retain(|window| window.hwnd != hwnd);
+                if self.floating.contains(&hwnd) {
+                    self.floating.retain(|h| *h != hwnd);
+                    continue;
+                }
                 removed_windows.push(hwnd);
             }
         }
         // Add any new windows
         let mut added_windows = Vec::new();
         for window in win_api::window::get_all() {
+            if self.is_ignored(&window) && !self.windows.iter().any(|w| w.hwnd == window.hwnd) {
+                continue;
+            }
             if self.windows.iter().any(|w| w.hwnd == window.hwnd) {
                 // Remove the old window state
                 self.windows.retain(|w| w.hwnd != window.hwnd);
-            } else {
+            } else if !self.floating.contains(&window.hwnd) {
                 added_windows.push(window.hwnd);
             }
             self.windows.push(window);
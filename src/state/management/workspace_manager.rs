@@ -1,13 +1,89 @@
 use crate::data::common::direction::Direction;
-use crate::data::workspace::Workspace;
+use crate::data::workspace::{ScratchpadWindow, Workspace};
+use windows::Win32::Foundation::HWND;
 
 pub struct WorkspaceManager {
     workspaces: Vec<Workspace>,
+    // Windows that are shown on every workspace of their monitor
+    sticky: Vec<HWND>,
+    // Hidden pseudo-workspace which is not bound to any monitor
+    scratchpad: Vec<ScratchpadWindow>,
 }
 
 impl WorkspaceManager {
     pub fn new(workspaces: Vec<Workspace>) -> Self {
-        Self { workspaces }
+        Self {
+            workspaces,
+            sticky: Vec::new(),
+            scratchpad: Vec::new(),
+        }
+    }
+
+    pub fn in_scratchpad(&self, hwnd: &HWND) -> bool {
+        self.scratchpad.iter().any(|window| window.hwnd == *hwnd)
+    }
+
+    pub fn add_to_scratchpad(&mut self, hwnd: HWND, name: Option<String>) {
+        self.remove_from_scratchpad(&hwnd);
+        self.scratchpad.push(ScratchpadWindow {
+            hwnd,
+            name,
+            visible: false,
+        });
+    }
+
+    pub fn remove_from_scratchpad(&mut self, hwnd: &HWND) {
+        self.scratchpad.retain(|window| window.hwnd != *hwnd);
+    }
+
+    pub fn visible_scratchpad_hwnds(&self) -> Vec<HWND> {
+        self.scratchpad
+            .iter()
+            .filter(|window| window.visible)
+            .map(|window| window.hwnd)
+            .collect()
+    }
+
+    pub fn set_scratchpad_visible(&mut self, hwnd: &HWND, visible: bool) {
+        self.scratchpad
+            .iter_mut()
+            .filter(|window| window.hwnd == *hwnd)
+            .for_each(|window| window.visible = visible);
+    }
+
+    /*
+       Pick the scratchpad window a toggle applies to. Named toggles only match windows stored under
+       that name, otherwise a currently shown window is preferred over the most recently stored one
+    */
+    pub fn scratchpad_target(&self, name: &Option<String>) -> Option<(HWND, bool)> {
+        let candidates: Vec<&ScratchpadWindow> = self
+            .scratchpad
+            .iter()
+            .filter(|window| name.is_none() || window.name == *name)
+            .collect();
+        candidates
+            .iter()
+            .find(|window| window.visible)
+            .or(candidates.last())
+            .map(|window| (window.hwnd, window.visible))
+    }
+
+    pub fn is_sticky(&self, hwnd: &HWND) -> bool {
+        self.sticky.contains(hwnd)
+    }
+
+    // Forget sticky and scratchpad windows that no longer exist
+    pub fn retain_windows(&mut self, hwnds: &[HWND]) {
+        self.sticky.retain(|hwnd| hwnds.contains(hwnd));
+        self.scratchpad
+            .retain(|window| hwnds.contains(&window.hwnd));
+    }
+
+    pub fn set_sticky(&mut self, hwnd: HWND, sticky: bool) {
+        self.sticky.retain(|h| *h != hwnd);
+        if sticky {
+            self.sticky.push(hwnd);
+        }
     }
 
     pub fn group_in_direction(&self, group: usize, direction: &Direction) -> Option<usize> {
@@ -34,6 +110,10 @@ impl WorkspaceManager {
         self.workspaces[workspace_id].groups.clone()
     }
 
+    pub fn is_active(&self, workspace_id: usize) -> bool {
+        self.workspaces[workspace_id].active
+    }
+
     pub fn toggle_active(&mut self, workspace_id: usize) {
         let workspace = self.get_workspace(workspace_id);
         workspace.active = !workspace.active;
@@ -1,2 +1,2 @@
-mod init;
+pub mod init;
 pub mod management;
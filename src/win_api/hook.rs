@@ -1,7 +1,7 @@
 use std::process::exit;
 
 use log::error;
-use windows::Win32::{Foundation::{HWND, LPARAM, LRESULT, WPARAM}, UI::{Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK}, WindowsAndMessaging::{CallNextHookEx, SetWindowsHookExA, UnhookWindowsHookEx, EVENT_OBJECT_DESTROY, EVENT_SYSTEM_FOREGROUND, HHOOK, WINDOWS_HOOK_ID, WINEVENT_OUTOFCONTEXT}}};
+use windows::Win32::{Foundation::{HWND, LPARAM, LRESULT, WPARAM}, UI::{Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK}, WindowsAndMessaging::{CallNextHookEx, SetWindowsHookExA, UnhookWindowsHookEx, EVENT_OBJECT_DESTROY, EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND, HHOOK, WINDOWS_HOOK_ID, WINEVENT_OUTOFCONTEXT}}};
 
 use super::misc::handle_result;
 
@@ -13,6 +13,11 @@ pub fn set_event_hook(callback: unsafe extern "system" fn(HWINEVENTHOOK, u32, HW
   return unsafe { SetWinEventHook(EVENT_SYSTEM_FOREGROUND, EVENT_OBJECT_DESTROY, None, Some(callback), 0, 0, WINEVENT_OUTOFCONTEXT) };
 }
 
+// Title changes are far from the other window events, a single range would include every location change
+pub fn set_title_hook(callback: unsafe extern "system" fn(HWINEVENTHOOK, u32, HWND, i32, i32, u32, u32)) -> HWINEVENTHOOK {
+  return unsafe { SetWinEventHook(EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_NAMECHANGE, None, Some(callback), 0, 0, WINEVENT_OUTOFCONTEXT) };
+}
+
 pub fn unset_window_hook(hook: HHOOK) {
   let result = unsafe { UnhookWindowsHookEx(hook) };
   if result.is_err() {
@@ -2,12 +2,17 @@ use std::ffi::CString;
 
 use log::error;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    GetKeyNameTextA, MapVirtualKeyA, VkKeyScanA, MAPVK_VK_TO_VSC,
+    GetKeyNameTextA, GetKeyboardLayout, MapVirtualKeyA, MapVirtualKeyExW, SendInput, INPUT,
+    INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, MAPVK_VK_TO_CHAR,
+    MAPVK_VK_TO_VSC, VIRTUAL_KEY,
 };
-
-use crate::data::key::{KEY_ALT, KEY_CONTROL, KEY_SHIFT, KEY_SPACE, WINDOWS_KEY_CODE};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
 
 pub fn get_key_name(key_code: i32) -> String {
+    // Only the config commands run elsewhere, where there is no keyboard layout to ask
+    if cfg!(not(windows)) {
+        return String::new();
+    }
     let scan_code = unsafe { MapVirtualKeyA(key_code as u32, MAPVK_VK_TO_VSC) };
     let mut buffer = vec![0; 32];
     let result = unsafe { GetKeyNameTextA((scan_code << 16) as i32, &mut buffer) };
@@ -20,19 +25,44 @@ pub fn get_key_name(key_code: i32) -> String {
     return CString::new(buffer).unwrap().into_string().unwrap();
 }
 
-pub fn get_key_code(key: &str) -> i32 {
-    let result = match key.to_uppercase().as_str() {
-        "SPACE" => KEY_SPACE,
-        "WIN" => WINDOWS_KEY_CODE,
-        "CTRL" => KEY_CONTROL,
-        "ALT" => KEY_ALT,
-        "SHIFT" => KEY_SHIFT,
-        _ => unsafe { VkKeyScanA(key.as_bytes()[0] as i8) as i32 },
+// Character typed by the key on the keyboard layout of the foreground window, ignoring modifiers
+pub fn key_character(key_code: i32) -> Option<char> {
+    let character = unsafe {
+        let thread_id = GetWindowThreadProcessId(GetForegroundWindow(), None);
+        let layout = GetKeyboardLayout(thread_id);
+        MapVirtualKeyExW(key_code as u32, MAPVK_VK_TO_CHAR, layout)
     };
-
-    if result == 0 {
-        error!("Failed to fetch key code for character {}", key);
+    // The high bit marks dead keys, which still type their character once combined
+    let character = character & 0x7FFFFFFF;
+    if character == 0 {
+        return None;
     }
+    return char::from_u32(character);
+}
 
-    return result;
+// Unassigned virtual key, pressing it has no effect besides being seen by the system
+pub const MASK_KEY_CODE: u32 = 0xE8;
+
+/*
+   Tap a key nobody listens to, so Windows doesn't treat the release of a modifier as the
+   modifier being pressed on its own (a lone WIN opens the start menu, a lone ALT the menu bar)
+*/
+pub fn send_mask_key() {
+    let inputs: Vec<INPUT> = [KEYBD_EVENT_FLAGS(0), KEYEVENTF_KEYUP]
+        .iter()
+        .map(|flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(MASK_KEY_CODE as u16),
+                    dwFlags: *flags,
+                    ..Default::default()
+                },
+            },
+        })
+        .collect();
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        error!("Failed to send mask key");
+    }
 }
@@ -3,6 +3,7 @@ use std::process::exit;
 use log::error;
 use windows::core::Error;
 use windows::Win32::Foundation::{GetLastError, HMODULE, WIN32_ERROR};
+use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
 use windows::Win32::System::Threading::{AttachThreadInput, GetCurrentThreadId};
 
@@ -39,3 +40,11 @@ pub fn detach_thread(target_thread: u32) {
 pub fn get_main_module() -> HMODULE {
     return handle_result(unsafe { GetModuleHandleA(None) });
 }
+
+// The executable uses the windows subsystem, command line output needs the console it was started from
+pub fn attach_console() {
+    if cfg!(not(windows)) {
+        return;
+    }
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
@@ -1,5 +1,6 @@
 pub mod keyboard;
 pub mod misc;
 pub mod monitor;
+pub mod overlay;
 pub mod window;
 pub mod hook;
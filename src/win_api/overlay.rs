@@ -0,0 +1,281 @@
+use std::sync::{Mutex, Once};
+
+use log::error;
+use windows::core::PCSTR;
+use windows::Win32::Foundation::{
+    GetLastError, COLORREF, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, RECT, WIN32_ERROR, WPARAM,
+};
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, CreatePen, CreateSolidBrush, DeleteObject, DrawTextA, EndPaint, FillRect, GetDC,
+    GetStockObject, InvalidateRect, Rectangle, ReleaseDC, RoundRect, SelectObject, SetBkMode,
+    SetTextColor, ANSI_FIXED_FONT, DT_CALCRECT, DT_CENTER, DT_END_ELLIPSIS, DT_LEFT, DT_NOPREFIX,
+    DT_SINGLELINE, DT_VCENTER, HDC, NULL_BRUSH, PAINTSTRUCT, PS_INSIDEFRAME, TRANSPARENT,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DefWindowProcA, DestroyWindow, GetClientRect, RegisterClassA, SetLayeredWindowAttributes,
+    SetWindowPos, ShowWindow, HWND_TOPMOST, LWA_COLORKEY, SWP_NOACTIVATE, SWP_SHOWWINDOW, SW_HIDE,
+    WM_PAINT, WNDCLASSA,
+};
+
+use crate::win_api::misc::handle_result;
+
+// Pixels painted in this color are fully transparent
+const TRANSPARENT_COLOR: u32 = 0xFF00FF;
+const CORNER_RADIUS: i32 = 16;
+const TEXT_COLOR: u32 = 0xFFFFFF;
+const TEXT_BACKGROUND_COLOR: u32 = 0x202020;
+// Space between the edge of a text overlay and its text
+const TEXT_PADDING: i32 = 12;
+
+pub enum OverlayContent {
+    Border {
+        color: u32,
+        width: i32,
+        rounded: bool,
+    },
+    Tabs {
+        titles: Vec<String>,
+        active: usize,
+        active_color: u32,
+        inactive_color: u32,
+    },
+    // Lines of text in a fixed width font, so columns padded with spaces line up
+    Text {
+        lines: Vec<String>,
+    },
+}
+
+static OVERLAY_CONTENT: Mutex<Vec<(isize, OverlayContent)>> = Mutex::new(Vec::new());
+static REGISTER_CLASS: Once = Once::new();
+
+pub fn register_class(instance: HMODULE, class_name: &str) {
+    extern "system" fn overlay_callback(
+        window: HWND,
+        message: u32,
+        w_param: WPARAM,
+        l_param: LPARAM,
+    ) -> LRESULT {
+        match message {
+            WM_PAINT => {
+                paint(window);
+                LRESULT(0)
+            }
+            _ => unsafe { DefWindowProcA(window, message, w_param, l_param) },
+        }
+    }
+
+    REGISTER_CLASS.call_once(|| {
+        let window_class = WNDCLASSA {
+            lpfnWndProc: Some(overlay_callback),
+            hInstance: HINSTANCE(instance.0),
+            lpszClassName: PCSTR(class_name.as_ptr()),
+            ..Default::default()
+        };
+        let result = unsafe { RegisterClassA(&window_class) };
+        if result == 0 {
+            let error: WIN32_ERROR = unsafe { GetLastError() };
+            error!("Error registering overlay class: {:?}", error);
+        }
+    });
+}
+
+pub fn set_transparent_color(hwnd: HWND) {
+    handle_result(unsafe {
+        SetLayeredWindowAttributes(hwnd, rgb(TRANSPARENT_COLOR), 0, LWA_COLORKEY)
+    });
+}
+
+pub fn set_content(hwnd: HWND, content: OverlayContent) {
+    let mut overlays = OVERLAY_CONTENT.lock().unwrap();
+    overlays.retain(|(overlay, _)| *overlay != hwnd.0);
+    overlays.push((hwnd.0, content));
+    drop(overlays);
+    let _ = unsafe { InvalidateRect(hwnd, None, true) };
+}
+
+pub fn show(hwnd: HWND, position: RECT) {
+    let width: i32 = position.right - position.left;
+    let height: i32 = position.bottom - position.top;
+    handle_result(unsafe {
+        SetWindowPos(
+            hwnd,
+            HWND_TOPMOST,
+            position.left,
+            position.top,
+            width,
+            height,
+            SWP_NOACTIVATE | SWP_SHOWWINDOW,
+        )
+    });
+    let _ = unsafe { InvalidateRect(hwnd, None, true) };
+}
+
+pub fn hide(hwnd: HWND) {
+    let _ = unsafe { ShowWindow(hwnd, SW_HIDE) };
+}
+
+// Destroy an overlay which isn't needed anymore, along with its content
+pub fn destroy(hwnd: HWND) {
+    OVERLAY_CONTENT
+        .lock()
+        .unwrap()
+        .retain(|(overlay, _)| *overlay != hwnd.0);
+    if let Err(error) = unsafe { DestroyWindow(hwnd) } {
+        error!("Error destroying overlay window: {:?}", error);
+    }
+}
+
+fn paint(hwnd: HWND) {
+    let overlays = OVERLAY_CONTENT.lock().unwrap();
+    let content = overlays
+        .iter()
+        .find(|(overlay, _)| *overlay == hwnd.0)
+        .map(|(_, content)| content);
+    unsafe {
+        let mut paint_struct = PAINTSTRUCT::default();
+        let hdc = BeginPaint(hwnd, &mut paint_struct);
+        let mut client_rect = RECT::default();
+        let _ = GetClientRect(hwnd, &mut client_rect);
+        let background = CreateSolidBrush(rgb(TRANSPARENT_COLOR));
+        FillRect(hdc, &client_rect, background);
+        let _ = DeleteObject(background);
+        match content {
+            Some(OverlayContent::Border {
+                color,
+                width,
+                rounded,
+            }) => {
+                let pen = CreatePen(PS_INSIDEFRAME, *width, rgb(*color));
+                let old_pen = SelectObject(hdc, pen);
+                let old_brush = SelectObject(hdc, GetStockObject(NULL_BRUSH));
+                if *rounded {
+                    let _ = RoundRect(
+                        hdc,
+                        client_rect.left,
+                        client_rect.top,
+                        client_rect.right,
+                        client_rect.bottom,
+                        CORNER_RADIUS,
+                        CORNER_RADIUS,
+                    );
+                } else {
+                    let _ = Rectangle(
+                        hdc,
+                        client_rect.left,
+                        client_rect.top,
+                        client_rect.right,
+                        client_rect.bottom,
+                    );
+                }
+                SelectObject(hdc, old_brush);
+                SelectObject(hdc, old_pen);
+                let _ = DeleteObject(pen);
+            }
+            Some(OverlayContent::Tabs {
+                titles,
+                active,
+                active_color,
+                inactive_color,
+            }) => {
+                paint_tabs(
+                    hdc,
+                    &client_rect,
+                    titles,
+                    *active,
+                    *active_color,
+                    *inactive_color,
+                );
+            }
+            Some(OverlayContent::Text { lines }) => paint_text(hdc, &client_rect, lines),
+            None => {}
+        }
+        let _ = EndPaint(hwnd, &paint_struct);
+    }
+}
+
+// Lay the titles out side by side, each tab taking an equal share of the strip
+unsafe fn paint_tabs(
+    hdc: HDC,
+    rect: &RECT,
+    titles: &[String],
+    active: usize,
+    active_color: u32,
+    inactive_color: u32,
+) {
+    if titles.is_empty() {
+        return;
+    }
+    let tab_width = (rect.right - rect.left) / titles.len() as i32;
+    SetBkMode(hdc, TRANSPARENT);
+    SetTextColor(hdc, rgb(TEXT_COLOR));
+    for (index, title) in titles.iter().enumerate() {
+        let left = rect.left + tab_width * index as i32;
+        let mut tab_rect = RECT {
+            left,
+            right: left + tab_width,
+            ..*rect
+        };
+        let color = match index == active {
+            true => active_color,
+            false => inactive_color,
+        };
+        let brush = CreateSolidBrush(rgb(color));
+        FillRect(hdc, &tab_rect, brush);
+        let _ = DeleteObject(brush);
+        let mut text = title.clone().into_bytes();
+        DrawTextA(
+            hdc,
+            &mut text,
+            &mut tab_rect,
+            DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS,
+        );
+    }
+}
+
+// Size of a text overlay showing the lines, padding included
+pub fn text_size(lines: &[String]) -> (i32, i32) {
+    let mut rect = RECT::default();
+    unsafe {
+        let hdc = GetDC(None);
+        let old_font = SelectObject(hdc, GetStockObject(ANSI_FIXED_FONT));
+        let mut text = lines.join("\n").into_bytes();
+        DrawTextA(
+            hdc,
+            &mut text,
+            &mut rect,
+            DT_LEFT | DT_NOPREFIX | DT_CALCRECT,
+        );
+        SelectObject(hdc, old_font);
+        ReleaseDC(None, hdc);
+    }
+    return (
+        rect.right - rect.left + 2 * TEXT_PADDING,
+        rect.bottom - rect.top + 2 * TEXT_PADDING,
+    );
+}
+
+unsafe fn paint_text(hdc: HDC, rect: &RECT, lines: &[String]) {
+    let brush = CreateSolidBrush(rgb(TEXT_BACKGROUND_COLOR));
+    FillRect(hdc, rect, brush);
+    let _ = DeleteObject(brush);
+    let old_font = SelectObject(hdc, GetStockObject(ANSI_FIXED_FONT));
+    SetBkMode(hdc, TRANSPARENT);
+    SetTextColor(hdc, rgb(TEXT_COLOR));
+    let mut text_rect = RECT {
+        left: rect.left + TEXT_PADDING,
+        top: rect.top + TEXT_PADDING,
+        right: rect.right - TEXT_PADDING,
+        bottom: rect.bottom - TEXT_PADDING,
+    };
+    let mut text = lines.join("\n").into_bytes();
+    DrawTextA(hdc, &mut text, &mut text_rect, DT_LEFT | DT_NOPREFIX);
+    SelectObject(hdc, old_font);
+}
+
+// Convert 0xRRGGBB into the 0x00BBGGRR layout expected by GDI
+fn rgb(color: u32) -> COLORREF {
+    let red = (color >> 16) & 0xFF;
+    let green = (color >> 8) & 0xFF;
+    let blue = color & 0xFF;
+    COLORREF(red | (green << 8) | (blue << 16))
+}
@@ -1,10 +1,13 @@
 use std::ffi::CString;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Mutex;
 
 use crate::data::common::event::Event;
 use log::error;
-use windows::core::PCSTR;
+use windows::core::{HSTRING, PCSTR, PSTR};
 use windows::Win32::Foundation::{
-    GetLastError, BOOL, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT, WIN32_ERROR, WPARAM,
+    CloseHandle, GetLastError, BOOL, HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT,
+    WIN32_ERROR, WPARAM,
 };
 use windows::Win32::Graphics::Dwm::{
     DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS, DWMWA_VISIBLE_FRAME_BORDER_THICKNESS,
@@ -12,47 +15,97 @@ use windows::Win32::Graphics::Dwm::{
 };
 use windows::Win32::Graphics::Gdi::ValidateRect;
 use windows::Win32::System::StationsAndDesktops::EnumDesktopWindows;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameA, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
 use windows::Win32::UI::HiDpi::{
     GetDpiForWindow, SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
 };
 use windows::Win32::UI::Shell::{
-    Shell_NotifyIconA, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NOTIFYICONDATAA,
+    Shell_NotifyIconA, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_MODIFY, NOTIFYICONDATAA,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    BringWindowToTop, CreatePopupMenu, CreateWindowExA, DefWindowProcA, DestroyMenu, GetCursorPos,
-    GetForegroundWindow, GetMessageA, GetWindowInfo, GetWindowLongA, GetWindowPlacement,
-    GetWindowRect, GetWindowTextA, GetWindowThreadProcessId, InsertMenuA, LoadCursorW, LoadIconW,
-    PostMessageA, PostQuitMessage, RegisterClassA, SetForegroundWindow, SetWindowPos, ShowWindow,
-    TrackPopupMenu, CS_HREDRAW, CS_OWNDC, CS_VREDRAW, GWL_EXSTYLE, GWL_STYLE, HCURSOR, IDC_ARROW,
-    IDI_APPLICATION, MF_STRING, MSG, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOCOPYBITS,
-    SWP_NOSENDCHANGING, SW_MAXIMIZE, SW_RESTORE, SW_SHOWMINNOACTIVE, TPM_BOTTOMALIGN,
-    TPM_RIGHTALIGN, TPM_RIGHTBUTTON, WINDOWINFO, WINDOWPLACEMENT, WINDOW_EX_STYLE,
-    WINDOW_LONG_PTR_INDEX, WINDOW_STYLE, WM_APP, WM_CLOSE, WM_COMMAND, WM_DESTROY, WM_PAINT,
-    WM_RBUTTONUP, WM_USER, WNDCLASSA, WS_OVERLAPPEDWINDOW, WS_SIZEBOX, WS_VISIBLE,
+    BringWindowToTop, CreatePopupMenu, CreateWindowExA, DefWindowProcA, DestroyMenu, GetAncestor,
+    GetClassNameA, GetCursorPos, GetForegroundWindow, GetMessageA, GetWindowInfo, GetWindowLongA,
+    GetWindowPlacement, GetWindowRect, GetWindowTextA, GetWindowThreadProcessId, InsertMenuA,
+    KillTimer, LoadCursorW, LoadIconW, MessageBoxW, PostMessageA, PostQuitMessage, RegisterClassA,
+    SetCursorPos, SetForegroundWindow, SetTimer, SetWindowPos, ShowWindow, TrackPopupMenu,
+    WindowFromPoint, CS_HREDRAW, CS_OWNDC, CS_VREDRAW, GA_ROOT, GWL_EXSTYLE, GWL_STYLE, HCURSOR,
+    IDC_ARROW, IDI_APPLICATION, MB_ICONERROR, MB_OK, MF_BYPOSITION, MF_STRING, MSG,
+    SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOCOPYBITS, SWP_NOSENDCHANGING, SW_MAXIMIZE, SW_RESTORE,
+    SW_SHOWMINNOACTIVE, TPM_BOTTOMALIGN, TPM_RIGHTALIGN, TPM_RIGHTBUTTON, WINDOWINFO,
+    WINDOWPLACEMENT, WINDOW_EX_STYLE, WINDOW_LONG_PTR_INDEX, WINDOW_STYLE, WM_APP, WM_CLOSE,
+    WM_COMMAND, WM_DESTROY, WM_PAINT, WM_RBUTTONUP, WM_USER, WNDCLASSA, WS_OVERLAPPEDWINDOW,
+    WS_SIZEBOX, WS_VISIBLE,
 };
 
-use crate::data::window::Window;
+use crate::data::window::{Window, WindowMetadata};
 use crate::win_api::misc::{attach_thread, detach_thread, handle_result};
 
 pub fn set_dpi_awareness() {
     let _ = unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
 }
 
+// Fenestra has no console, so problems keeping it from running are shown in a message box
+pub fn show_error(title: &str, message: &str) {
+    let _ = unsafe {
+        MessageBoxW(
+            None,
+            &HSTRING::from(message),
+            &HSTRING::from(title),
+            MB_OK | MB_ICONERROR,
+        )
+    };
+}
+
+// Window the tray icon belongs to, kept to update its tooltip
+static TRAY_WINDOW: AtomicIsize = AtomicIsize::new(0);
+const TRAY_TOOLTIP: &str = "Fenestra";
+
 pub fn system_tray(hwnd: &HWND) {
-    // let tooltip_text = "WindowManager".as_bytes();
-    // let mut tooltip: [i8;128] = [0;128];
-    // for (&x, p) in tooltip_text.iter().zip(tooltip.iter_mut()) {
-    //     *p = x as i8;
-    // }
-
-    let data = NOTIFYICONDATAA {
-        cbSize: Default::default(),
-        hWnd: *hwnd,
+    TRAY_WINDOW.store(hwnd.0, Ordering::SeqCst);
+    let mut data = tray_icon_data(*hwnd, TRAY_TOOLTIP);
+    data.uFlags = NIF_TIP | NIF_ICON | NIF_MESSAGE;
+    data.uCallbackMessage = WM_APP + 1;
+    data.hIcon = unsafe { LoadIconW(HMODULE::default(), IDI_APPLICATION).unwrap() };
+    let result = unsafe { Shell_NotifyIconA(NIM_ADD, &data) };
+    if !result.as_bool() {
+        error!("Unable to create system tray icon");
+    }
+}
+
+// Shows the active binding mode in the tooltip of the tray icon
+pub fn set_tray_mode(mode: Option<&str>) {
+    let hwnd = HWND(TRAY_WINDOW.load(Ordering::SeqCst));
+    if hwnd.0 == 0 {
+        return;
+    }
+    let tooltip = match mode {
+        Some(mode) => format!("{} - mode: {}", TRAY_TOOLTIP, mode),
+        None => String::from(TRAY_TOOLTIP),
+    };
+    let mut data = tray_icon_data(hwnd, &tooltip);
+    data.uFlags = NIF_TIP;
+    let result = unsafe { Shell_NotifyIconA(NIM_MODIFY, &data) };
+    if !result.as_bool() {
+        error!("Unable to update the system tray tooltip");
+    }
+}
+
+fn tray_icon_data(hwnd: HWND, tooltip: &str) -> NOTIFYICONDATAA {
+    // The tooltip is truncated to fit, leaving room for the terminating null
+    let mut tip: [i8; 128] = [0; 128];
+    for (&byte, tip_byte) in tooltip.as_bytes().iter().take(127).zip(tip.iter_mut()) {
+        *tip_byte = byte as i8;
+    }
+    return NOTIFYICONDATAA {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAA>() as u32,
+        hWnd: hwnd,
         uID: 0,
-        uFlags: NIF_TIP | NIF_ICON | NIF_MESSAGE,
-        uCallbackMessage: WM_APP + 1,
-        hIcon: unsafe { LoadIconW(HMODULE::default(), IDI_APPLICATION).unwrap() },
-        szTip: [0; 128],
+        uFlags: Default::default(),
+        uCallbackMessage: 0,
+        hIcon: Default::default(),
+        szTip: tip,
         dwState: Default::default(),
         dwStateMask: Default::default(),
         szInfo: [0; 256],
@@ -62,23 +115,32 @@ pub fn system_tray(hwnd: &HWND) {
         guidItem: Default::default(),
         hBalloonIcon: Default::default(),
     };
-    let result = unsafe { Shell_NotifyIconA(NIM_ADD, &data) };
-    if !result.as_bool() {
-        error!("Unable to create system tray icon");
-    }
 }
 
+// Command identifiers of the tray menu items
+const TRAY_QUIT: u32 = WM_USER + 1;
+const TRAY_RELOAD_CONFIG: u32 = WM_USER + 2;
+
 pub fn display_tray_menu(hwnd: HWND) {
     let mut item_text: String = String::from("Quit\0");
+    let mut reload_text: String = String::from("Reload config\0");
     unsafe {
         let context_menu = handle_result(CreatePopupMenu());
         handle_result(InsertMenuA(
             context_menu,
             0,
             MF_STRING,
-            (WM_USER + 1) as usize,
+            TRAY_QUIT as usize,
             PCSTR::from_raw(item_text.as_mut_ptr()),
         ));
+        // Inserted by position, so it ends up above Quit
+        handle_result(InsertMenuA(
+            context_menu,
+            0,
+            MF_BYPOSITION | MF_STRING,
+            TRAY_RELOAD_CONFIG as usize,
+            PCSTR::from_raw(reload_text.as_mut_ptr()),
+        ));
 
         let mut cursor_position = POINT { x: 0, y: 0 };
         handle_result(GetCursorPos(&mut cursor_position));
@@ -123,8 +185,10 @@ pub fn register_class(instance: HMODULE, class_name: &str) {
                 LRESULT(0)
             }
             WM_COMMAND => {
-                if w_param.0 == (WM_USER + 1) as usize {
+                if w_param.0 == TRAY_QUIT as usize {
                     unsafe { PostQuitMessage(100) };
+                } else if w_param.0 == TRAY_RELOAD_CONFIG as usize {
+                    send_event_message(Event::reload_config());
                 }
                 LRESULT(0)
             }
@@ -197,6 +261,37 @@ pub fn focus(hwnd: &HWND) -> bool {
     }
 }
 
+pub fn get_cursor_position() -> POINT {
+    let mut point = POINT::default();
+    handle_result(unsafe { GetCursorPos(&mut point) });
+    return point;
+}
+
+pub fn hwnd_from_point(point: POINT) -> HWND {
+    unsafe {
+        let hwnd = WindowFromPoint(point);
+        if hwnd == HWND::default() {
+            return hwnd;
+        }
+        return GetAncestor(hwnd, GA_ROOT);
+    }
+}
+
+pub fn set_cursor_position(point: POINT) {
+    let result = unsafe { SetCursorPos(point.x, point.y) };
+    if result.is_err() {
+        error!("Unable to move the cursor to {:?}", point);
+    }
+}
+
+pub fn set_timer(timer_id: usize, milliseconds: u32) -> usize {
+    return unsafe { SetTimer(None, timer_id, milliseconds, None) };
+}
+
+pub fn kill_timer(timer_id: usize) {
+    let _ = unsafe { KillTimer(None, timer_id) };
+}
+
 pub fn get_style(handle: &HWND) -> i32 {
     return get_window_info(*handle, GWL_STYLE);
 }
@@ -210,6 +305,12 @@ pub fn get_dpi(hwnd: HWND) -> u32 {
 }
 
 static mut WINDOWS: Vec<Window> = Vec::new();
+/*
+   Executable names by window and process, so the process is only opened the first time a window is
+   looked at. Keyed by both, as window handles and process ids get reused once they are gone
+*/
+static EXECUTABLE_NAMES: Mutex<Vec<(isize, u32, String)>> = Mutex::new(Vec::new());
+
 pub fn get_all() -> Vec<Window> {
     unsafe {
         WINDOWS.clear();
@@ -228,13 +329,18 @@ pub fn get_all() -> Vec<Window> {
         let mut windows = WINDOWS.clone();
         windows.sort_by(|a, b| a.hwnd.0.partial_cmp(&b.hwnd.0).unwrap());
         Vec::dedup(&mut windows);
+        // Forget the windows which are gone
+        EXECUTABLE_NAMES
+            .lock()
+            .unwrap()
+            .retain(|(hwnd, _, _)| windows.iter().any(|window| window.hwnd.0 == *hwnd));
         return windows;
     }
 }
 
 pub fn get_window(hwnd: HWND) -> Option<Window> {
     let title: String = get_window_title(hwnd);
-    if title.is_empty() || title.to_lowercase().contains("settings") {
+    if title.is_empty() {
         return None;
     }
     let style = get_style(&hwnd);
@@ -269,6 +375,8 @@ pub fn get_window(hwnd: HWND) -> Option<Window> {
     let (thread_id, process_id) = get_window_thread_id(hwnd);
     let window_placement: WINDOWPLACEMENT = get_window_placement(hwnd);
     let dpi = get_dpi(hwnd);
+    let class_name = get_class_name(hwnd);
+    let executable = cached_executable_name(hwnd, process_id);
     return Some(Window {
         title,
         hwnd,
@@ -282,9 +390,21 @@ pub fn get_window(hwnd: HWND) -> Option<Window> {
         dpi,
         style,
         extended_style,
+        class_name,
+        executable,
     });
 }
 
+// Metadata of any window, including the ones which aren't managed
+pub fn get_window_metadata(hwnd: HWND) -> WindowMetadata {
+    let (_, process_id) = get_window_thread_id(hwnd);
+    return WindowMetadata {
+        executable: cached_executable_name(hwnd, process_id),
+        class_name: get_class_name(hwnd),
+        title: get_window_title(hwnd),
+    };
+}
+
 pub fn set_position(hwnd: &HWND, position: RECT, dpi_change: bool) {
     let width: i32 = position.right - position.left;
     let height: i32 = position.bottom - position.top;
@@ -351,6 +471,61 @@ pub fn get_window_title(handle: HWND) -> String {
     return CString::new(buffer).unwrap().to_string_lossy().to_string();
 }
 
+pub fn get_class_name(handle: HWND) -> String {
+    let mut buffer = vec![0; 256];
+    let result = unsafe { GetClassNameA(handle, &mut buffer) };
+    if result == 0 {
+        return String::new();
+    }
+    buffer.truncate(result as usize);
+    return String::from_utf8_lossy(&buffer).to_string();
+}
+
+fn cached_executable_name(hwnd: HWND, process_id: u32) -> String {
+    let mut executable_names = EXECUTABLE_NAMES.lock().unwrap();
+    let cached = executable_names
+        .iter()
+        .find(|(cached_hwnd, cached_process_id, _)| {
+            *cached_hwnd == hwnd.0 && *cached_process_id == process_id
+        });
+    if let Some((_, _, executable)) = cached {
+        return executable.clone();
+    }
+    let executable = get_executable_name(process_id);
+    executable_names.retain(|(cached_hwnd, _, _)| *cached_hwnd != hwnd.0);
+    executable_names.push((hwnd.0, process_id, executable.clone()));
+    return executable;
+}
+
+// File name of the executable a process was started from, empty when the process can't be opened
+pub fn get_executable_name(process_id: u32) -> String {
+    let process = match unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }
+    {
+        Ok(process) => process,
+        Err(_) => return String::new(),
+    };
+    let mut buffer = vec![0u8; 1024];
+    let mut size = buffer.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameA(
+            process,
+            PROCESS_NAME_WIN32,
+            PSTR(buffer.as_mut_ptr()),
+            &mut size,
+        )
+    };
+    let _ = unsafe { CloseHandle(process) };
+    if result.is_err() {
+        return String::new();
+    }
+    buffer.truncate(size as usize);
+    let path = String::from_utf8_lossy(&buffer).to_string();
+    return match path.rsplit_once('\\') {
+        Some((_, file_name)) => file_name.to_string(),
+        None => path,
+    };
+}
+
 fn get_extended_style(handle: HWND) -> i32 {
     return get_window_info(handle, GWL_EXSTYLE);
 }
@@ -380,6 +555,12 @@ fn get_rect(hwnd: HWND) -> (RECT, RECT) {
     return (rect, shadow_rect);
 }
 
+pub fn get_frame_bounds(hwnd: HWND) -> RECT {
+    let mut frame_bounds = RECT::default();
+    get_ext_attr(hwnd, DWMWA_EXTENDED_FRAME_BOUNDS, &mut frame_bounds);
+    return frame_bounds;
+}
+
 fn get_window_thread_id(handle: HWND) -> (u32, u32) {
     let mut process_id = 0;
     let result = unsafe { GetWindowThreadProcessId(handle, Some(&mut process_id)) };